@@ -0,0 +1,338 @@
+//! Command-line interface for compiling loot table scripts.
+//!
+//! Build with `cargo build --features cli`.
+
+use rna::{LootTable, MeguScript, Resolver};
+use rna::conflict::ConflictPolicy;
+use rna::datapack::{Datapack, DatapackError};
+use rna::code;
+use rna::diagnostic::{Diagnostic, Diagnostics, Severity, ToDiagnostics};
+use rna::utils::{self, CompilerOptions, MeguError};
+use rna::render::{self, Painted, ColorMode};
+use std::path::{Path, PathBuf};
+use std::{env, fmt, io, process};
+
+const USAGE: &str = "\
+Usage:
+    rna compile <script> [options]          Compile a single script into vanilla loot table
+    rna merge <script>... [options]         Merge several scripts into one vanilla loot table
+    rna datapack <directory> [options]      Compile every script inside a datapack
+    rna explain <code>                      Explain an error code (i.e. RNA0001)
+
+Options:
+    -r, --root <directory>    Add search root for `extend` field (can be repeated)
+    -e, --extension <ext>     Treat files with this extension as script too (can be repeated)
+    -o, --output <path>       Write the result to this path instead of stdout
+                              (output directory for `datapack`, default to the datapack itself)
+        --strict              Reject unknown fields
+        --all-errors          Report every error in a script instead of stopping at the first one
+        --conflict <policy>   What to do when two scripts define the same pool key
+                              (error, warn, keep-first, keep-last; default to warn)
+        --sort                Sort pools by key
+        --message-format <format>
+                              Print warnings and errors as `human` text or `json` lines
+        --color <when>        Color human messages (auto, always, never)
+    -h, --help                Print this message
+
+For `datapack`, these options override `compiler_options` inside `pack.mcmeta`.";
+
+/// Exit code when compilation fail
+const EXIT_FAILURE: i32 = 1;
+/// Exit code when arguments are invalid
+const EXIT_USAGE: i32 = 2;
+
+enum Command {
+	Compile(PathBuf),
+	Merge(Vec<PathBuf>),
+	Datapack(PathBuf),
+	Explain(String),
+	Help
+}
+
+struct Arguments {
+	command: Command,
+	resolver: Resolver,
+	output: Option<PathBuf>,
+	/// Options from command-line flags, they override `compiler_options` inside `pack.mcmeta`
+	options: CompilerOptions,
+	/// Print warnings and errors as JSON lines instead of colored text
+	json: bool,
+	color: ColorMode
+}
+
+/// Error that stop the command
+enum CliError {
+	Usage(String),
+	Megu(MeguError),
+	Datapack(DatapackError),
+	Io(PathBuf, io::Error)
+}
+
+impl From<MeguError> for CliError {
+	fn from(error: MeguError) -> CliError {
+		CliError::Megu(error)
+	}
+}
+
+impl From<DatapackError> for CliError {
+	fn from(error: DatapackError) -> CliError {
+		CliError::Datapack(error)
+	}
+}
+
+impl fmt::Display for CliError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+			CliError::Megu(error) => write!(f, "{}", error),
+			CliError::Datapack(error) => write!(f, "{}", error),
+			CliError::Io(path, error) => write!(f, "[{}] {}", Painted::new(path.display()).green(), error)
+		}
+	}
+}
+
+fn parse_policy(value: &str) -> Result<ConflictPolicy, CliError> {
+	value.parse().map_err(|value| CliError::Usage(format!("'{}' is not a valid conflict policy.", value)))
+}
+
+fn parse_arguments(arguments: Vec<String>) -> Result<Arguments, CliError> {
+	let mut arguments = arguments.into_iter();
+	let subcommand = arguments.next();
+
+	let mut inputs = Vec::new();
+	let mut resolver = Resolver::new();
+	let mut output = None;
+	let mut options = CompilerOptions::default();
+	let mut json = false;
+	let mut color = ColorMode::Auto;
+
+	while let Some(argument) = arguments.next() {
+		let mut value = |name: &str| arguments.next().ok_or_else(|| CliError::Usage(format!("'{}' require a value.", name)));
+
+		match argument.as_str() {
+			"-r" | "--root" => resolver.push(value(&argument)?),
+			"-e" | "--extension" => resolver = resolver.extension(value(&argument)?),
+			"-o" | "--output" => output = Some(PathBuf::from(value(&argument)?)),
+			"--strict" => options.strict = Some(true),
+			"--all-errors" => options.collect_errors = Some(true),
+			"--conflict" => options.conflict_policy = Some(parse_policy(&value(&argument)?)?),
+			"--sort" => options.sort_pools = Some(true),
+			"--message-format" => json = parse_format(&value(&argument)?)?,
+			"--color" => color = parse_color(&value(&argument)?)?,
+			"-h" | "--help" => return Ok(Arguments { command: Command::Help, resolver, output, options, json, color }),
+			flag if flag.starts_with('-') => return Err(CliError::Usage(format!("Unknown option '{}'.", flag))),
+			_ => inputs.push(PathBuf::from(argument))
+		}
+	}
+
+	let command = match subcommand.as_deref() {
+		None | Some("-h") | Some("--help") | Some("help") => Command::Help,
+		Some("compile") if inputs.len() == 1 => Command::Compile(inputs.remove(0)),
+		Some("compile") => return Err(CliError::Usage("'compile' require exactly one script.".to_string())),
+		Some("merge") if !inputs.is_empty() => Command::Merge(inputs),
+		Some("merge") => return Err(CliError::Usage("'merge' require at least one script.".to_string())),
+		Some("datapack") if inputs.len() == 1 => Command::Datapack(inputs.remove(0)),
+		Some("datapack") => return Err(CliError::Usage("'datapack' require exactly one directory.".to_string())),
+		Some("explain") if inputs.len() == 1 => Command::Explain(inputs.remove(0).to_string_lossy().into_owned()),
+		Some("explain") => return Err(CliError::Usage("'explain' require exactly one error code.".to_string())),
+		Some(other) => return Err(CliError::Usage(format!("Unknown command '{}'.", other)))
+	};
+
+	Ok(Arguments { command, resolver, output, options, json, color })
+}
+
+/// Find `--message-format` before parsing the rest, so invalid arguments are reported in that format too
+fn prescan_format(arguments: &[String]) -> bool {
+	arguments
+		.windows(2)
+		.rev()
+		.find(|pair| pair[0] == "--message-format")
+		.is_some_and(|pair| parse_format(&pair[1]).unwrap_or_default())
+}
+
+/// Return `true` for JSON message format
+fn parse_format(value: &str) -> Result<bool, CliError> {
+	match value {
+		"human" => Ok(false),
+		"json" => Ok(true),
+		_ => Err(CliError::Usage(format!("'{}' is not a valid message format.", value)))
+	}
+}
+
+fn parse_color(value: &str) -> Result<ColorMode, CliError> {
+	match value {
+		"auto" => Ok(ColorMode::Auto),
+		"always" => Ok(ColorMode::Ansi),
+		"never" => Ok(ColorMode::Plain),
+		_ => Err(CliError::Usage(format!("'{}' is not a valid color mode.", value)))
+	}
+}
+
+fn print_warnings(diagnostics: Diagnostics, json: bool) {
+	if json {
+		diagnostics.to_diagnostics().iter().for_each(|diagnostic| eprintln!("{}", diagnostic.to_json()));
+		return;
+	}
+
+	for warning in diagnostics {
+		eprintln!("{}: {}", Painted::new("warning").yellow().bold(), warning);
+	}
+}
+
+/// Read and merge `paths` into a single loot table
+fn compile(paths: &[PathBuf], arguments: &Arguments) -> Result<LootTable, CliError> {
+	let scripts: Result<Vec<MeguScript>, MeguError> = paths
+		.iter()
+		.map(|path| utils::interpret_file_with(path, &arguments.resolver, arguments.options.parse_options()))
+		.collect();
+
+	let mut diagnostics = Diagnostics::default();
+	let result = utils::merge_with(&scripts?, &arguments.resolver, &arguments.options.merge_options(), &mut diagnostics);
+	print_warnings(diagnostics, arguments.json);
+
+	Ok(LootTable::from(&result?))
+}
+
+fn emit(table: &LootTable, output: Option<&Path>) -> Result<(), CliError> {
+	match output {
+		Some(path) => table.write(path).map_err(|error| CliError::Io(path.to_path_buf(), error)),
+		None => {
+			let content = table.to_string_pretty().map_err(|error| CliError::Io(PathBuf::from("<stdout>"), error.into()))?;
+			println!("{}", content);
+			Ok(())
+		}
+	}
+}
+
+fn compile_datapack(root: &Path, arguments: &Arguments) -> Result<(), CliError> {
+	let output = arguments.output.clone().unwrap_or_else(|| root.to_path_buf());
+	let datapack = Datapack::open_with(root, arguments.resolver.detector())?;
+
+	let mut diagnostics = Diagnostics::default();
+	let result = datapack.compile_with(&arguments.resolver, &arguments.options, &mut diagnostics);
+	print_warnings(diagnostics, arguments.json);
+
+	for (path, table) in result? {
+		let target = output.join(path);
+		emit(&table, Some(&target))?;
+		eprintln!("{} {}", Painted::new("compiled").green().bold(), target.display());
+	}
+
+	Ok(())
+}
+
+fn run(arguments: Arguments) -> Result<(), CliError> {
+	match &arguments.command {
+		Command::Help => {
+			println!("{}", USAGE);
+			Ok(())
+		},
+		Command::Compile(path) => emit(&compile(std::slice::from_ref(path), &arguments)?, arguments.output.as_deref()),
+		Command::Merge(paths) => emit(&compile(paths, &arguments)?, arguments.output.as_deref()),
+		Command::Datapack(root) => compile_datapack(root, &arguments),
+		Command::Explain(value) => {
+			let explanation = code::explain(value).ok_or_else(|| CliError::Usage(format!("'{}' is not a known error code.", value)))?;
+			println!("{}", explanation);
+			Ok(())
+		}
+	}
+}
+
+impl ToDiagnostics for CliError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			CliError::Usage(message) => vec![Diagnostic::new(Severity::Error, code::USAGE, message.clone())],
+			CliError::Megu(error) => error.to_diagnostics(),
+			CliError::Datapack(error) => error.to_diagnostics(),
+			CliError::Io(path, error) => {
+				let mut diagnostic = Diagnostic::new(Severity::Error, code::IO, error.to_string());
+				diagnostic.file = Some(path.clone());
+				vec![diagnostic]
+			}
+		}
+	}
+}
+
+fn fail(error: CliError, json: bool) -> ! {
+	if json {
+		error.to_diagnostics().iter().for_each(|diagnostic| eprintln!("{}", diagnostic.to_json()));
+	}
+	else {
+		eprintln!("{}: {}", Painted::new("error").red().bold(), error);
+
+		let mut codes: Vec<String> = error.to_diagnostics().into_iter().map(|diagnostic| diagnostic.code).collect();
+		codes.sort();
+		codes.dedup();
+		for code in codes.iter().filter(|code| code::explain(code).is_some()) {
+			eprintln!("For more information about this error, try `rna explain {}`.", code);
+		}
+	}
+
+	let code = match error {
+		CliError::Usage(_) => EXIT_USAGE,
+		_ => EXIT_FAILURE
+	};
+	process::exit(code);
+}
+
+fn main() {
+	render::set_color_mode(ColorMode::Auto);
+	let arguments: Vec<String> = env::args().skip(1).collect();
+	let json = prescan_format(&arguments);
+	let arguments = match parse_arguments(arguments) {
+		Ok(arguments) => arguments,
+		Err(error) => fail(error, json)
+	};
+
+	render::set_color_mode(arguments.color);
+	let json = arguments.json;
+	if let Err(error) = run(arguments) {
+		fail(error, json);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn arguments(values: &[&str]) -> Result<Arguments, CliError> {
+		parse_arguments(values.iter().map(|value| value.to_string()).collect())
+	}
+
+	#[test]
+	fn parse_merge_command() {
+		let result = arguments(&["merge", "a.ult", "b.ult", "-r", "library", "--conflict", "error", "--strict"]).ok().unwrap();
+
+		assert!(matches!(result.command, Command::Merge(ref paths) if paths.len() == 2));
+		assert_eq!(result.resolver.roots(), [PathBuf::from("library")]);
+		assert_eq!(result.options.conflict_policy, Some(ConflictPolicy::Error));
+		assert_eq!(result.options.strict, Some(true));
+		assert_eq!(result.color, ColorMode::Auto);
+	}
+
+	#[test]
+	fn parse_explain_command() {
+		let result = arguments(&["explain", "rna0001"]).ok().unwrap();
+		assert!(matches!(result.command, Command::Explain(ref value) if value == "rna0001"));
+	}
+
+	#[test]
+	fn reject_invalid_arguments() {
+		assert!(matches!(arguments(&["compile"]), Err(CliError::Usage(_))));
+		assert!(matches!(arguments(&["compile", "a.ult", "--conflict", "random"]), Err(CliError::Usage(_))));
+		assert!(matches!(arguments(&["explode"]), Err(CliError::Usage(_))));
+		assert!(matches!(arguments(&["explain"]), Err(CliError::Usage(_))));
+		assert!(matches!(arguments(&["compile", "a.ult", "--color", "rainbow"]), Err(CliError::Usage(_))));
+	}
+
+	#[test]
+	fn prescan_message_format() {
+		let prescan = |values: &[&str]| prescan_format(&values.iter().map(|value| value.to_string()).collect::<Vec<_>>());
+
+		assert!(prescan(&["compile", "--unknown", "--message-format", "json"]));
+		assert!(!prescan(&["compile", "--message-format", "json", "--message-format", "human"]));
+		assert!(!prescan(&["compile", "--message-format", "xml"]));
+		assert!(!prescan(&["compile", "--message-format"]));
+		assert_eq!(CliError::Usage(String::new()).to_diagnostics()[0].code, code::USAGE);
+	}
+}
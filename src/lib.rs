@@ -8,7 +8,12 @@
 //! let loot_a = utils::interpret_file("test/loot_a.ult", "resource").unwrap();
 //! let loot_b = utils::interpret_file("test/loot_b.ult", "resource").unwrap();
 //! 
-//! let merged_loot = utils::merge(&[loot_a, loot_b], "resource").unwrap();
+//! let (merged_loot, warnings) = utils::merge(&[loot_a, loot_b], "resource").unwrap();
+//! for warning in warnings.warnings() {
+//!     eprintln!("{}", warning);
+//! }
+//! let loot_table = rna::LootTable::from(&merged_loot);
+//! loot_table.write("data/minecraft/loot_tables/blocks/stone.json").unwrap();
 //! ```
 
 
@@ -19,7 +24,9 @@ pub use megu::{
 	MeguScript,
 	MeguDrop,
 	Namespace,
-	Extension
+	Extension,
+	Resolver,
+	LootTable
 };
 
 /// Module containg every errors type in this crate
@@ -30,11 +37,12 @@ pub mod error {
 	pub use crate::megu::extension::ExtensionError;
 	pub use crate::megu::drop::DropTypeError;
 	pub use crate::util::MetaError;
+	pub use crate::megu::datapack::DatapackError;
 }
 
 /// MeguScript module
 pub mod script {
-	pub use crate::megu::script::{MeguScript, ScriptFormat, ScriptFormatError, ReadError};
+	pub use crate::megu::script::{MeguScript, ScriptFormat, ScriptFormatError, ReadError, ParseOptions};
 }
 
 /// Namespace module
@@ -44,7 +52,8 @@ pub mod namespace {
 
 /// Extension module
 pub mod extension {
-	pub use crate::megu::extension::{Extension, ExtensionError};
+	pub use crate::megu::extension::{Extension, ExtensionError, Origin};
+	pub use crate::megu::resolver::Resolver;
 }
 
 /// MeguDrop module
@@ -52,8 +61,84 @@ pub mod drop {
 	pub use crate::megu::drop::{MeguDrop, DropType, DropFormat, DropTypeError};
 }
 
+/// Number provider module
+pub mod number {
+	pub use crate::megu::number::NumberProvider;
+}
+
+/// Embedded vanilla loot table database (require `vanilla` feature)
+#[cfg(feature = "vanilla")]
+pub mod vanilla {
+	pub use crate::megu::vanilla::{get, paths};
+}
+
+/// Vanilla loot table module
+pub mod loot_table {
+	pub use crate::megu::loot_table::{LootTable, LootPool, LootEntry};
+}
+
+/// Decompiler module for converting vanilla loot table into loot table script
+pub mod decompile {
+	pub use crate::megu::decompile::{decompile, decompile_file, location_of};
+}
+
+/// Builder module for creating MeguScript and MeguDrop in Rust
+pub mod builder {
+	pub use crate::megu::builder::{ScriptBuilder, DropBuilder};
+}
+
+/// Pattern module for `remove` field
+pub mod pattern {
+	pub use crate::megu::pattern::{Pattern, Removal};
+}
+
+/// Conflict handling module for merging
+pub mod conflict {
+	pub use crate::megu::conflict::{ConflictPolicy, Conflict, ScriptSource};
+}
+
+/// Stable error codes used by `Diagnostic`
+pub mod code {
+	pub use crate::megu::code::*;
+}
+
+/// Diagnostics module for warnings emit during merging and machine-readable error reporting
+pub mod diagnostic {
+	pub use crate::megu::diagnostic::{Diagnostics, Warning, Diagnostic, Severity, ToDiagnostics};
+}
+
+/// Datapack module for compiling every loot table script inside a datapack
+pub mod datapack {
+	pub use crate::megu::datapack::{Datapack, DatapackError, MergedTable, Provenance, merge_datapacks};
+}
+
+/// Source location module for errors
+pub mod span {
+	pub use crate::megu::span::Span;
+}
+
+/// Loot table script detection module
+pub mod detect {
+	pub use crate::megu::detect::{Detector, ScriptKind};
+}
+
+/// Loot condition module
+pub mod condition {
+	pub use crate::megu::condition::{Condition, ConditionError};
+}
+
+/// Loot function module
+pub mod function {
+	pub use crate::megu::function::{LootFunction, Function, FunctionError};
+}
+
+/// Terminal color control for errors and warnings
+pub mod render {
+	pub use crate::megu::render::{ColorMode, Painted, render, plain, set_color_mode, color_mode};
+}
+
 /// Utility module
 pub mod utils {
-	pub use crate::megu::{interpret_file, merge, MeguResult, MeguError};
-	pub use crate::util::{check_meta, is_loot_table_script, MetaError};
+	pub use crate::megu::{interpret_file, interpret_file_with, merge, merge_with, MergeOptions, MeguResult, MeguError};
+	pub use crate::util::{check_meta, read_meta, is_loot_table_script, MetaError, CompilerOption, CompilerOptions, OptionError};
 }
\ No newline at end of file
@@ -0,0 +1,178 @@
+use super::{MeguScript, MeguDrop, DropType, Namespace, Extension, Pattern, NumberProvider, Condition, LootFunction};
+use super::drop::DropTypeError;
+use indexmap::IndexMap;
+
+/// Builder for `MeguScript`, create it with `MeguScript::builder()`
+///
+/// # Examples
+/// ```
+/// # use rna::{MeguScript, MeguDrop, Namespace};
+/// # use rna::drop::DropType;
+/// # use rna::pattern::Pattern;
+/// let ruby = MeguDrop::builder(DropType::Item)
+///     .name("boomber:ruby")
+///     .weight(5)
+///     .build()
+///     .unwrap();
+///
+/// let script = MeguScript::builder()
+///     .kind("minecraft:block")
+///     .pool(Namespace::new("boomber", "ruby"), ruby)
+///     .remove(Pattern::decode("minecraft:minecraft/blocks/stone").unwrap())
+///     .build();
+///
+/// assert_eq!(script.kind(), Some("minecraft:block"));
+/// assert_eq!(script.pools().len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScriptBuilder {
+	kind: Option<String>,
+	extend: Option<Extension>,
+	pools: IndexMap<Namespace, MeguDrop>,
+	remove: Vec<Pattern>
+}
+
+impl ScriptBuilder {
+	/// Set `type` field (i.e. `minecraft:block`)
+	pub fn kind(mut self, kind: impl Into<String>) -> ScriptBuilder {
+		self.kind = Some(kind.into());
+		self
+	}
+
+	/// Set `extend` field, use `Extension::get_extension()` or `Resolver::resolve()` to find one
+	pub fn extend(mut self, extension: Extension) -> ScriptBuilder {
+		self.extend = Some(extension);
+		self
+	}
+
+	/// Add `drop` with the given key, replacing any drop that already use this key
+	pub fn pool(mut self, key: Namespace, drop: MeguDrop) -> ScriptBuilder {
+		self.pools.insert(key, drop);
+		self
+	}
+
+	/// Add `pattern` to `remove` field
+	pub fn remove(mut self, pattern: impl Into<Pattern>) -> ScriptBuilder {
+		self.remove.push(pattern.into());
+		self
+	}
+
+	pub fn build(self) -> MeguScript {
+		MeguScript::new(self.kind, self.extend, self.pools, self.remove)
+	}
+}
+
+/// Builder for `MeguDrop`, create it with `MeguDrop::builder()`
+///
+/// `build()` check the same rules as `MeguDrop::from_drop_format()`.
+///
+/// # Examples
+/// Unsafe type (`alternatives`, `group` and `sequence`) need to be marked as unsafe
+/// ```
+/// # use rna::MeguDrop;
+/// # use rna::drop::DropType;
+/// let stone = MeguDrop::builder(DropType::Item).name("minecraft:stone").build().unwrap();
+///
+/// assert!(MeguDrop::builder(DropType::Alternatives).child(stone.clone()).build().is_err());
+/// assert!(MeguDrop::builder(DropType::Alternatives).child(stone).r#unsafe(true).build().is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropBuilder {
+	drop: MeguDrop
+}
+
+impl DropBuilder {
+	pub(crate) fn new(kind: DropType) -> DropBuilder {
+		let drop = MeguDrop::new(kind, None, None, Vec::default(), Vec::default(), false);
+		DropBuilder { drop }
+	}
+
+	pub fn name(mut self, name: impl Into<String>) -> DropBuilder {
+		self.drop.name = Some(name.into());
+		self
+	}
+
+	/// Add `child` to `children` field
+	pub fn child(mut self, child: MeguDrop) -> DropBuilder {
+		self.drop.children.get_or_insert_with(Vec::default).push(child);
+		self
+	}
+
+	pub fn condition(mut self, condition: Condition) -> DropBuilder {
+		self.drop.conditions.push(condition);
+		self
+	}
+
+	pub fn function(mut self, function: impl Into<LootFunction>) -> DropBuilder {
+		self.drop.functions.push(function.into());
+		self
+	}
+
+	/// Set `"unsafe"` keyword
+	pub fn r#unsafe(mut self, value: bool) -> DropBuilder {
+		self.drop.r#unsafe = value;
+		self
+	}
+
+	pub fn rolls(mut self, rolls: impl Into<NumberProvider>) -> DropBuilder {
+		self.drop.rolls = Some(rolls.into());
+		self
+	}
+
+	pub fn bonus_rolls(mut self, bonus_rolls: impl Into<NumberProvider>) -> DropBuilder {
+		self.drop.bonus_rolls = Some(bonus_rolls.into());
+		self
+	}
+
+	pub fn weight(mut self, weight: u32) -> DropBuilder {
+		self.drop.weight = Some(weight);
+		self
+	}
+
+	pub fn quality(mut self, quality: i32) -> DropBuilder {
+		self.drop.quality = Some(quality);
+		self
+	}
+
+	/// Create MeguDrop
+	///
+	/// This method can fail when unsafe type doesn't have `"unsafe"` keyword or safe type have it,
+	/// or when one of the children is `DropType::Pool`.
+	pub fn build(self) -> Result<MeguDrop, DropTypeError> {
+		let kind = self.drop.kind;
+		MeguDrop::check_unsafe(kind, self.drop.r#unsafe, kind.name())?;
+		MeguDrop::check_children(self.drop.children.as_deref().unwrap_or_default())?;
+
+		Ok(self.drop)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::script::ScriptFormat;
+
+	#[test]
+	fn build_same_script_as_parser() {
+		let value = r#"{"type":"minecraft:block","pools":{"boomber:ruby":{"type":"minecraft:item","name":"boomber:ruby","rolls":2}},"remove":["minecraft:minecraft/blocks/stone/*"]}"#;
+		let format: ScriptFormat = serde_json::from_str(value).unwrap();
+		let expect = MeguScript::from(format);
+
+		let ruby = MeguDrop::builder(DropType::Item).name("boomber:ruby").rolls(2.0).build().unwrap();
+		let script = MeguScript::builder()
+			.kind("minecraft:block")
+			.pool(Namespace::new("boomber", "ruby"), ruby)
+			.remove(Pattern::decode("minecraft:minecraft/blocks/stone/*").unwrap())
+			.build();
+
+		assert_eq!(script, expect);
+	}
+
+	#[test]
+	fn reject_unsafe_keyword_on_safe_type() {
+		assert_eq!(
+			MeguDrop::builder(DropType::Item).r#unsafe(true).build(),
+			Err(DropTypeError::NotAllow("minecraft:item".to_string()))
+		);
+	}
+}
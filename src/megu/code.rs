@@ -0,0 +1,305 @@
+//! Stable error codes used by `Diagnostic`.
+//!
+//! Codes are never reused or renumbered, new codes are always added at the end.
+//! Use `explain()` to get a long-form explanation of a code.
+
+/// Unsafe drop type without `"unsafe": true` (or safe type with it)
+pub const UNSAFE_NOT_ALLOW: &str = "RNA0001";
+/// `type` field is not a valid drop type
+pub const INVALID_DROP_TYPE: &str = "RNA0002";
+/// Namespace contain invalid character
+pub const INVALID_NAMESPACE: &str = "RNA0003";
+/// Namespace contain more than one colon
+pub const TOO_MANY_COLONS: &str = "RNA0004";
+/// Internal error from `regex` crate
+pub const REGEX: &str = "RNA0005";
+/// `extend` field refer to extension that cannot be found
+pub const EXTENSION_NOT_FOUND: &str = "RNA0006";
+/// `extend` chain loop back to itself
+pub const EXTEND_CYCLE: &str = "RNA0007";
+/// Unknown field in strict mode
+pub const UNKNOWN_FIELD: &str = "RNA0008";
+/// Invalid JSON syntax or structure
+pub const INVALID_JSON: &str = "RNA0009";
+/// I/O error while reading or writing file
+pub const IO: &str = "RNA0010";
+/// File does not exists
+pub const NOT_EXIST: &str = "RNA0011";
+/// Path is not a file
+pub const NOT_A_FILE: &str = "RNA0012";
+/// Two scripts define the same pool key with different drop
+pub const POOL_CONFLICT: &str = "RNA0013";
+/// Pattern in `remove` field doesn't match any drop
+pub const UNMATCHED_REMOVE: &str = "RNA0014";
+/// Unknown option in `compiler_options` field
+pub const UNKNOWN_OPTION: &str = "RNA0015";
+/// `pack.mcmeta` doesn't have `compiler_options` field
+pub const NO_COMPILER_OPTIONS: &str = "RNA0016";
+/// Known compiler option with invalid value
+pub const INVALID_OPTION: &str = "RNA0017";
+/// Condition is malformed or known condition has invalid field
+pub const INVALID_CONDITION: &str = "RNA0018";
+/// Function is malformed or known function has invalid field
+pub const INVALID_FUNCTION: &str = "RNA0019";
+/// Invalid command-line arguments
+pub const USAGE: &str = "RNA0020";
+
+/// Long-form explanation of `code` with an example and how to fix it, `None` for unknown code
+///
+/// Lookup is case-insensitive and the `RNA` prefix is optional.
+///
+/// # Examples
+/// ```
+/// # use rna::code;
+/// assert!(code::explain(code::UNSAFE_NOT_ALLOW).unwrap().contains("\"unsafe\": true"));
+/// assert_eq!(code::explain("rna0001"), code::explain("0001"));
+/// assert_eq!(code::explain("RNA9999"), None);
+/// ```
+pub fn explain(code: &str) -> Option<&'static str> {
+	let code = code.trim().to_ascii_uppercase();
+	let code = if code.starts_with("RNA") { code } else { format!("RNA{}", code) };
+
+	EXPLANATIONS.iter().find(|(key, _)| *key == code).map(|(_, explanation)| *explanation)
+}
+
+/// Every code that has an explanation, in numeric order
+pub fn codes() -> impl Iterator<Item = &'static str> {
+	EXPLANATIONS.iter().map(|(code, _)| *code)
+}
+
+const EXPLANATIONS: &[(&str, &str)] = &[
+	(UNSAFE_NOT_ALLOW, r#"Composite drop type is used without `"unsafe": true`, or a simple type is used with it.
+
+`minecraft:alternatives`, `minecraft:group` and `minecraft:sequence` nest other drops in
+their `children` field. Children don't have a namespace key, so other scripts cannot
+`extend`, replace or `remove` them one by one; the whole composite drop is owned by the
+script that define it. The order of `children` also decide which one is picked, which is
+easy to break by accident. Because of that, these types must be marked explicitly.
+
+Erroneous example:
+
+    {
+        "pools": {
+            "boomber:ruby": {
+                "type": "minecraft:alternatives",
+                "children": [
+                    { "type": "minecraft:item", "name": "minecraft:diamond" },
+                    { "type": "minecraft:item", "name": "minecraft:stone" }
+                ]
+            }
+        }
+    }
+
+Add `"unsafe": true` next to `type`:
+
+    "boomber:ruby": {
+        "type": "minecraft:alternatives",
+        "unsafe": true,
+        "children": [ ... ]
+    }
+
+If you don't need the nested structure, split the children into separate pools instead,
+each of them can then be merged and removed on its own. The keyword is rejected on every
+other type (i.e. `minecraft:item`), remove it there."#),
+	(INVALID_DROP_TYPE, r#"`type` field of a drop is not one of the vanilla entry types.
+
+Erroneous example:
+
+    "boomber:ruby": { "type": "minecraft:itme", "name": "minecraft:diamond" }
+
+Valid types are `minecraft:item`, `minecraft:tag`, `minecraft:loot_table`,
+`minecraft:dynamic`, `minecraft:empty` and the unsafe `minecraft:alternatives`,
+`minecraft:group` and `minecraft:sequence` (see RNA0001). The `minecraft:` prefix is optional:
+
+    "boomber:ruby": { "type": "minecraft:item", "name": "minecraft:diamond" }
+
+The unsafe `rna:pool` type hold a whole loot pool with its entries as `children`, so it can only
+be used directly inside `pools` field, never as a child of another drop."#),
+	(INVALID_NAMESPACE, r#"Namespace contain character that Minecraft doesn't allow.
+
+Only lowercase letters, digits, `_`, `-`, `.`, `/` and a single `:` may be used.
+
+Erroneous example:
+
+    "pools": { "Boomber:Ruby": { "type": "minecraft:empty" } }
+
+Use lowercase instead:
+
+    "pools": { "boomber:ruby": { "type": "minecraft:empty" } }"#),
+	(TOO_MANY_COLONS, r#"Namespace contain more than one colon.
+
+A namespace is `prefix:suffix`, the prefix is optional and default to `minecraft`.
+
+Erroneous example:
+
+    "extend": "boomber:gems:ruby"
+
+Use `/` to separate path segment instead:
+
+    "extend": "boomber:gems/ruby""#),
+	(REGEX, r#"Internal error while building a regular expression.
+
+This should never happen with a valid build of this library, please report it along with
+the script that cause it."#),
+	(EXTENSION_NOT_FOUND, r#"`extend` field refer to a script that cannot be found.
+
+`"extend": "boomber:ruby"` is looked up as `boomber/ruby.ult` (or any other script
+extension) inside every search root, then inside the embedded vanilla loot tables.
+
+Erroneous example:
+
+    { "extend": "boomber:rubyy", "pools": {} }
+
+Check the spelling, or add the directory that contain `boomber/ruby.ult` as a search
+root (i.e. `rna compile script.ult --root resource`)."#),
+	(EXTEND_CYCLE, r#"`extend` chain loop back to a script that is already being extended.
+
+Erroneous example:
+
+    // boomber/a.ult
+    { "extend": "boomber:b", "pools": {} }
+    // boomber/b.ult
+    { "extend": "boomber:a", "pools": {} }
+
+Move the shared pools into a third script and let both of them extend it."#),
+	(UNKNOWN_FIELD, r#"Script contain a field that isn't recognized, only reported in strict mode.
+
+Erroneous example:
+
+    "boomber:ruby": { "type": "minecraft:item", "condtions": [] }
+
+Fix the spelling (`conditions`), or remove the field. Strict mode is enabled with
+`--strict` or `{ "name": "strict" }` inside `compiler_options`."#),
+	(INVALID_JSON, r#"File is not valid JSON, or its structure doesn't match a loot table script.
+
+Erroneous example:
+
+    { "pools": { "boomber:ruby": { "type": "minecraft:empty", } } }
+
+Trailing commas and comments are not allowed. `pools` must be an object of drops and
+`remove` must be an array of patterns."#),
+	(IO, r#"File could not be read or written.
+
+Check that the file exists, that you have permission to access it and that the disk is not
+full. The underlying operating system error is included in the message."#),
+	(NOT_EXIST, r#"Given path does not exist.
+
+Check the path passed to `interpret_file()` or to the command-line, relative paths are
+resolved from the current directory."#),
+	(NOT_A_FILE, r#"Given path exist but it's not a file (i.e. a directory).
+
+Pass the script itself. Use `rna datapack <directory>` to compile every script inside a
+datapack."#),
+	(POOL_CONFLICT, r#"Two scripts define the same pool key while being merged.
+
+Erroneous example:
+
+    // first.ult
+    { "pools": { "boomber:ruby": { "type": "minecraft:item", "name": "minecraft:diamond" } } }
+    // second.ult
+    { "pools": { "boomber:ruby": { "type": "minecraft:item", "name": "minecraft:emerald" } } }
+
+By default the drop from the later script is kept and this warning is reported. Rename one
+of the keys, or choose what should happen with the `conflict_policy` option (`error`, `warn`,
+`keep-first` or `keep-last`)."#),
+	(UNMATCHED_REMOVE, r#"Pattern in `remove` field doesn't match any drop.
+
+This usually mean the drop was renamed (i.e. in a newer version of the script it extend).
+
+Erroneous example:
+
+    { "extend": "minecraft:blocks/stone", "pools": {}, "remove": ["minecraft:cobblestone"] }
+
+Update the pattern to the new key, `*` can be used as a wildcard (i.e. `boomber:*`)."#),
+	(UNKNOWN_OPTION, r#"`compiler_options` inside `pack.mcmeta` contain an option this library doesn't know.
+
+Known options are `conflict_policy`, `strict`, `collect_errors` and `sort_pools`. Unknown
+options are ignored, check the spelling if you expected it to have an effect."#),
+	(NO_COMPILER_OPTIONS, r#"`pack.mcmeta` doesn't have `compiler_options` field.
+
+Add it next to `pack`, an empty array is enough:
+
+    {
+        "pack": { "pack_format": 7, "description": "" },
+        "compiler_options": []
+    }"#),
+	(INVALID_OPTION, r#"Known compiler option has a value of the wrong type.
+
+Erroneous example:
+
+    "compiler_options": [{ "name": "strict", "value": "yes" }]
+
+`strict`, `collect_errors` and `sort_pools` take a boolean (or no value for `true`),
+`conflict_policy` take one of `error`, `warn`, `keep-first` or `keep-last`:
+
+    "compiler_options": [{ "name": "strict", "value": true }]"#),
+	(INVALID_CONDITION, r#"Entry in `conditions` field is not a valid loot condition.
+
+Every condition must be an object with `condition` field. Vanilla conditions (i.e.
+`minecraft:random_chance_with_looting`) are checked: required fields must exist and have the
+right type, even outside of strict mode. In strict mode no other field is allowed either,
+since Minecraft silently ignore a misspelled field; otherwise a condition with an extra field is
+kept as it is. Conditions from other namespaces are not checked.
+
+Erroneous example:
+
+    "conditions": [
+        { "condition": "minecraft:random_chance_with_looting", "chance": 0.025, "looting_multipler": 0.01 }
+    ]
+
+Fix the field name:
+
+    "conditions": [
+        { "condition": "minecraft:random_chance_with_looting", "chance": 0.025, "looting_multiplier": 0.01 }
+    ]"#),
+	(INVALID_FUNCTION, r#"Entry in `functions` field is not a valid loot function.
+
+Every function must be an object with `function` field. Vanilla functions (i.e.
+`minecraft:set_count`) are checked the same way as conditions (see RNA0018): required
+fields must exist and have the right type, and in strict mode no other field is allowed
+except `conditions`. Functions from other namespaces are not checked.
+
+Number providers are either a number, `{"min", "max", "type": "minecraft:uniform"}` or
+`{"n", "p", "type": "minecraft:binomial"}`, any other provider (i.e. `minecraft:score`) is
+kept as it is. `minecraft:looting_enchant` and `minecraft:set_damage` don't accept binomial.
+
+Erroneous example:
+
+    "functions": [
+        { "function": "minecraft:looting_enchant", "count": { "n": 2, "p": 0.5, "type": "minecraft:binomial" } }
+    ]
+
+Use a range instead:
+
+    "functions": [
+        { "function": "minecraft:looting_enchant", "count": { "min": 0, "max": 1 } }
+    ]"#),
+	(USAGE, r#"Command-line arguments are invalid.
+
+This happen when an option is unknown, an option is missing its value or has an invalid
+value, or a command is given the wrong number of inputs. `rna` exit with code `2` instead
+of `1` for this error. Run `rna --help` to see every command and option.
+
+Erroneous example:
+
+    rna compile ruby.ult --conflict random
+
+Use one of the listed values:
+
+    rna compile ruby.ult --conflict keep-last"#)
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_code_is_explained_in_order() {
+		let codes: Vec<&str> = codes().collect();
+		let expect: Vec<String> = (1..=codes.len()).map(|index| format!("RNA{:04}", index)).collect();
+
+		assert_eq!(codes, expect);
+		assert!(codes.iter().all(|code| explain(code).is_some()));
+		assert_eq!(explain(" rna0007 "), explain(EXTEND_CYCLE));
+	}
+}
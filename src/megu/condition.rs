@@ -0,0 +1,408 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::{Value, Map};
+use super::number::number;
+use super::fields::{Fields, FieldError, optional, object, integer, range};
+use super::script::ParseOptions;
+
+/// Loot condition (predicate) used in `conditions` field.
+///
+/// Known vanilla conditions are validated when they're read, anything else is kept as `Other`.
+/// Known condition with a field it doesn't use is also kept as `Other`, unless it's read with strict `ParseOptions`,
+/// but only when every field it does use is valid.
+///
+/// # Examples
+/// ```
+/// # use rna::condition::{Condition, ConditionError};
+/// # use rna::script::ParseOptions;
+/// let condition: Condition = serde_json::from_str(r#"{"condition":"minecraft:random_chance","chance":0.5}"#).unwrap();
+/// assert_eq!(condition, Condition::RandomChance { chance: 0.5 });
+///
+/// let typo = serde_json::json!({ "condition": "minecraft:random_chance_with_looting", "chance": 0.025, "looting_multipler": 0.01 });
+/// assert_eq!(Condition::from_value(&typo), Err(ConditionError::MissingField {
+///     condition: "minecraft:random_chance_with_looting".to_string(),
+///     field: "looting_multiplier".to_string()
+/// }));
+/// assert!(matches!(Condition::from_value_with(&typo, ParseOptions::strict()), Err(ConditionError::UnknownField { .. })));
+///
+/// let extra = serde_json::json!({ "condition": "minecraft:random_chance", "chance": 0.5, "comment": "rare" });
+/// assert_eq!(Condition::from_value(&extra), Ok(Condition::Other(extra.clone())));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+	/// Pass when any of `terms` pass
+	Alternative { terms: Vec<Condition> },
+	/// Pass when `term` doesn't pass
+	Inverted { term: Box<Condition> },
+	/// Pass when the entity was killed by a player, or wasn't when `inverse` is `true`
+	KilledByPlayer { inverse: Option<bool> },
+	/// Pass with `chance` probability (between `0.0` and `1.0`)
+	RandomChance { chance: f64 },
+	/// Pass with `chance + looting_level * looting_multiplier` probability
+	RandomChanceWithLooting { chance: f64, looting_multiplier: f64 },
+	/// Pass with `1 / explosion_radius` probability
+	SurvivesExplosion,
+	/// Check the tool with item `predicate`
+	MatchTool { predicate: Value },
+	/// Check `entity` (`this`, `killer`, `direct_killer` or `killer_player`) with entity `predicate`
+	EntityProperties { entity: String, predicate: Value },
+	/// Check the damage source with damage `predicate`
+	DamageSourceProperties { predicate: Value },
+	/// Check the location (moved by the offset) with location `predicate`
+	LocationCheck { predicate: Value, offset_x: Option<i32>, offset_y: Option<i32>, offset_z: Option<i32> },
+	/// Check the broken block and its block state `properties`
+	BlockStateProperty { block: String, properties: Option<Map<String, Value>> },
+	/// Pass with probability from `chances`, indexed by the level of `enchantment`
+	TableBonus { enchantment: String, chances: Vec<f64> },
+	/// Check the weather
+	WeatherCheck { raining: Option<bool>, thundering: Option<bool> },
+	/// Check scoreboard `scores` (number or `{"min", "max"}` range) of `entity`
+	EntityScores { entity: String, scores: Map<String, Value> },
+	/// Check the time of day, `value` is number or `{"min", "max"}` range
+	TimeCheck { value: Value, period: Option<i64> },
+	/// Refer to predicate file
+	Reference { name: String },
+	/// Condition that this library doesn't know, kept as it is
+	Other(Value)
+}
+
+const ENTITY_TARGETS: &[&str] = &["this", "killer", "direct_killer", "killer_player"];
+
+impl Condition {
+	/// Namespace of this condition (i.e. `minecraft:random_chance`), `None` for `Other` without `condition` field
+	pub fn name(&self) -> Option<&str> {
+		let name = match self {
+			Condition::Alternative { .. } => "minecraft:alternative",
+			Condition::Inverted { .. } => "minecraft:inverted",
+			Condition::KilledByPlayer { .. } => "minecraft:killed_by_player",
+			Condition::RandomChance { .. } => "minecraft:random_chance",
+			Condition::RandomChanceWithLooting { .. } => "minecraft:random_chance_with_looting",
+			Condition::SurvivesExplosion => "minecraft:survives_explosion",
+			Condition::MatchTool { .. } => "minecraft:match_tool",
+			Condition::EntityProperties { .. } => "minecraft:entity_properties",
+			Condition::DamageSourceProperties { .. } => "minecraft:damage_source_properties",
+			Condition::LocationCheck { .. } => "minecraft:location_check",
+			Condition::BlockStateProperty { .. } => "minecraft:block_state_property",
+			Condition::TableBonus { .. } => "minecraft:table_bonus",
+			Condition::WeatherCheck { .. } => "minecraft:weather_check",
+			Condition::EntityScores { .. } => "minecraft:entity_scores",
+			Condition::TimeCheck { .. } => "minecraft:time_check",
+			Condition::Reference { .. } => "minecraft:reference",
+			Condition::Other(value) => return value.get("condition").and_then(Value::as_str)
+		};
+
+		Some(name)
+	}
+
+	/// Convert this condition into JSON value the same way vanilla loot table write it
+	pub fn to_value(&self) -> Value {
+		let mut map = Map::new();
+		let mut insert = |key: &str, value: Value| {
+			map.insert(key.to_string(), value);
+		};
+
+		match self {
+			Condition::Other(value) => return value.clone(),
+			Condition::Alternative { terms } => insert("terms", terms.iter().map(Condition::to_value).collect()),
+			Condition::Inverted { term } => insert("term", term.to_value()),
+			Condition::KilledByPlayer { inverse } => optional(&mut insert, "inverse", *inverse),
+			Condition::RandomChance { chance } => insert("chance", number(*chance)),
+			Condition::RandomChanceWithLooting { chance, looting_multiplier } => {
+				insert("chance", number(*chance));
+				insert("looting_multiplier", number(*looting_multiplier));
+			},
+			Condition::SurvivesExplosion => (),
+			Condition::MatchTool { predicate } | Condition::DamageSourceProperties { predicate } => insert("predicate", predicate.clone()),
+			Condition::EntityProperties { entity, predicate } => {
+				insert("entity", Value::from(entity.as_str()));
+				insert("predicate", predicate.clone());
+			},
+			Condition::LocationCheck { predicate, offset_x, offset_y, offset_z } => {
+				insert("predicate", predicate.clone());
+				optional(&mut insert, "offsetX", *offset_x);
+				optional(&mut insert, "offsetY", *offset_y);
+				optional(&mut insert, "offsetZ", *offset_z);
+			},
+			Condition::BlockStateProperty { block, properties } => {
+				insert("block", Value::from(block.as_str()));
+				optional(&mut insert, "properties", properties.clone());
+			},
+			Condition::TableBonus { enchantment, chances } => {
+				insert("enchantment", Value::from(enchantment.as_str()));
+				insert("chances", chances.iter().copied().map(number).collect());
+			},
+			Condition::WeatherCheck { raining, thundering } => {
+				optional(&mut insert, "raining", *raining);
+				optional(&mut insert, "thundering", *thundering);
+			},
+			Condition::EntityScores { entity, scores } => {
+				insert("entity", Value::from(entity.as_str()));
+				insert("scores", Value::Object(scores.clone()));
+			},
+			Condition::TimeCheck { value, period } => {
+				insert("value", value.clone());
+				optional(&mut insert, "period", *period);
+			},
+			Condition::Reference { name } => insert("name", Value::from(name.as_str()))
+		}
+
+		if let Some(name) = self.name() {
+			map.insert("condition".to_string(), Value::from(name));
+		}
+		Value::Object(map)
+	}
+
+	/// Interpret JSON value as condition
+	///
+	/// This method can fail when `value` isn't an object with `condition` field,
+	/// or when a known condition is missing a field or has a field of the wrong type.
+	pub fn from_value(value: &Value) -> Result<Condition, ConditionError> {
+		Condition::from_value_with(value, ParseOptions::default())
+	}
+
+	/// Same as `from_value()` but known condition with an unknown field is only rejected when `options.strict` is set,
+	/// otherwise it's kept as `Other` so the field isn't lost. Fields it does use are still validated either way.
+	pub fn from_value_with(value: &Value, options: ParseOptions) -> Result<Condition, ConditionError> {
+		match Condition::read(value, options, true) {
+			Err(ConditionError::UnknownField { .. }) if !options.strict => Condition::read(value, options, false).map(|_| Condition::Other(value.clone())),
+			result => result
+		}
+	}
+
+	/// Unknown fields are ignored when `strict` is `false`
+	fn read(value: &Value, options: ParseOptions, strict: bool) -> Result<Condition, ConditionError> {
+		let (map, name) = match value.as_object().and_then(|map| Some((map, map.get("condition")?.as_str()?))) {
+			Some(result) => result,
+			None => return Err(ConditionError::Malformed(value.clone()))
+		};
+
+		let known = |known: &[&str]| Fields::new(name, map, "condition", known, strict, ConditionError::field);
+		let condition = match name.trim_start_matches("minecraft:") {
+			"alternative" => {
+				let terms = known(&["terms"])?.required("terms", "array of conditions", Value::as_array)?;
+				Condition::Alternative { terms: terms.iter().map(|term| Condition::from_value_with(term, options)).collect::<Result<_, _>>()? }
+			},
+			"inverted" => {
+				let term = known(&["term"])?.required("term", "condition", Some)?;
+				Condition::Inverted { term: Box::new(Condition::from_value_with(term, options)?) }
+			},
+			"killed_by_player" => Condition::KilledByPlayer { inverse: known(&["inverse"])?.optional("inverse", "boolean", Value::as_bool)? },
+			"random_chance" => Condition::RandomChance { chance: known(&["chance"])?.required("chance", "number", Value::as_f64)? },
+			"random_chance_with_looting" => {
+				let fields = known(&["chance", "looting_multiplier"])?;
+				Condition::RandomChanceWithLooting {
+					chance: fields.required("chance", "number", Value::as_f64)?,
+					looting_multiplier: fields.required("looting_multiplier", "number", Value::as_f64)?
+				}
+			},
+			"survives_explosion" => {
+				known(&[])?;
+				Condition::SurvivesExplosion
+			},
+			"match_tool" => Condition::MatchTool { predicate: known(&["predicate"])?.required("predicate", "object", object)?.clone() },
+			"damage_source_properties" => Condition::DamageSourceProperties { predicate: known(&["predicate"])?.required("predicate", "object", object)?.clone() },
+			"entity_properties" => {
+				let fields = known(&["entity", "predicate"])?;
+				Condition::EntityProperties {
+					entity: fields.required("entity", "this, killer, direct_killer or killer_player", entity_target)?.to_string(),
+					predicate: fields.required("predicate", "object", object)?.clone()
+				}
+			},
+			"location_check" => {
+				let fields = known(&["predicate", "offsetX", "offsetY", "offsetZ"])?;
+				Condition::LocationCheck {
+					predicate: fields.required("predicate", "object", object)?.clone(),
+					offset_x: fields.optional("offsetX", "integer", integer)?,
+					offset_y: fields.optional("offsetY", "integer", integer)?,
+					offset_z: fields.optional("offsetZ", "integer", integer)?
+				}
+			},
+			"block_state_property" => {
+				let fields = known(&["block", "properties"])?;
+				Condition::BlockStateProperty {
+					block: fields.required("block", "string", Value::as_str)?.to_string(),
+					properties: fields.optional("properties", "object", Value::as_object)?.cloned()
+				}
+			},
+			"table_bonus" => {
+				let fields = known(&["enchantment", "chances"])?;
+				let chances = fields.required("chances", "array of numbers", |value| value.as_array()?.iter().map(Value::as_f64).collect())?;
+				Condition::TableBonus { enchantment: fields.required("enchantment", "string", Value::as_str)?.to_string(), chances }
+			},
+			"weather_check" => {
+				let fields = known(&["raining", "thundering"])?;
+				Condition::WeatherCheck {
+					raining: fields.optional("raining", "boolean", Value::as_bool)?,
+					thundering: fields.optional("thundering", "boolean", Value::as_bool)?
+				}
+			},
+			"entity_scores" => {
+				let fields = known(&["entity", "scores"])?;
+				Condition::EntityScores {
+					entity: fields.required("entity", "this, killer, direct_killer or killer_player", entity_target)?.to_string(),
+					scores: fields.required("scores", "object", Value::as_object)?.clone()
+				}
+			},
+			"time_check" => {
+				let fields = known(&["value", "period"])?;
+				Condition::TimeCheck {
+					value: fields.required("value", "number or range", range)?.clone(),
+					period: fields.optional("period", "integer", Value::as_i64)?
+				}
+			},
+			"reference" => Condition::Reference { name: known(&["name"])?.required("name", "string", Value::as_str)?.to_string() },
+			_ => Condition::Other(value.clone())
+		};
+
+		Ok(condition)
+	}
+}
+
+fn entity_target(value: &Value) -> Option<&str> {
+	value.as_str().filter(|value| ENTITY_TARGETS.contains(value))
+}
+
+impl Serialize for Condition {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.to_value().serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for Condition {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Condition, D::Error> {
+		let value = Value::deserialize(deserializer)?;
+		Condition::from_value(&value).map_err(|error| D::Error::custom(crate::megu::render::plain(&error)))
+	}
+}
+
+/// Error type for `Condition`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionError {
+	/// Emit when condition is not an object with `condition` field
+	Malformed(Value),
+	/// Emit when known condition doesn't have a required field
+	MissingField {
+		condition: String,
+		field: String
+	},
+	/// Emit when field of known condition has the wrong type (i.e. `"chance": "high"`)
+	InvalidField {
+		condition: String,
+		field: String,
+		expected: String
+	},
+	/// Emit when known condition has a field that it doesn't use (usually a typo)
+	UnknownField {
+		condition: String,
+		field: String
+	}
+}
+
+impl ConditionError {
+	fn field(condition: &str, error: FieldError) -> ConditionError {
+		let condition = condition.to_string();
+		match error {
+			FieldError::Unknown(field) => ConditionError::UnknownField { condition, field: field.to_string() },
+			FieldError::Missing(field) => ConditionError::MissingField { condition, field: field.to_string() },
+			FieldError::Invalid { field, expected } => ConditionError::InvalidField { condition, field: field.to_string(), expected: expected.to_string() }
+		}
+	}
+
+	/// Namespace of the condition that cause this error
+	pub fn condition(&self) -> Option<&str> {
+		match self {
+			ConditionError::Malformed(_) => None,
+			ConditionError::MissingField { condition, .. } | ConditionError::InvalidField { condition, .. } | ConditionError::UnknownField { condition, .. } => Some(condition)
+		}
+	}
+}
+
+use std::fmt;
+use super::render::Paint;
+impl fmt::Display for ConditionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ConditionError::Malformed(value) => write!(f, "'{}' is not a valid condition, expected object with {} field.", value.to_string().cyan(), "condition".black().on_white()),
+			ConditionError::MissingField { condition, field } => write!(f, "'{}' condition require '{}' field.", condition.cyan(), field.red()),
+			ConditionError::InvalidField { condition, field, expected } => write!(f, "'{}' field of '{}' condition must be {}.", field.red(), condition.cyan(), expected),
+			ConditionError::UnknownField { condition, field } => write!(f, "Unknown field '{}' in '{}' condition.", field.red(), condition.cyan())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn read(value: Value) -> Result<Condition, ConditionError> {
+		Condition::from_value_with(&value, ParseOptions::strict())
+	}
+
+	#[test]
+	fn read_known_conditions() {
+		assert_eq!(read(json!({ "condition": "minecraft:killed_by_player" })), Ok(Condition::KilledByPlayer { inverse: None }));
+		assert_eq!(read(json!({ "condition": "survives_explosion" })), Ok(Condition::SurvivesExplosion));
+		assert_eq!(
+			read(json!({ "condition": "minecraft:inverted", "term": { "condition": "minecraft:random_chance", "chance": 0.5 } })),
+			Ok(Condition::Inverted { term: Box::new(Condition::RandomChance { chance: 0.5 }) })
+		);
+		assert_eq!(
+			read(json!({ "condition": "minecraft:table_bonus", "enchantment": "minecraft:fortune", "chances": [0.1, 0.25, 1] })),
+			Ok(Condition::TableBonus { enchantment: "minecraft:fortune".to_string(), chances: vec![0.1, 0.25, 1.0] })
+		);
+	}
+
+	#[test]
+	fn validate_known_conditions() {
+		assert_eq!(
+			read(json!({ "condition": "minecraft:random_chance_with_looting", "chance": 0.025, "looting_multipler": 0.01 })),
+			Err(ConditionError::UnknownField { condition: "minecraft:random_chance_with_looting".to_string(), field: "looting_multipler".to_string() })
+		);
+		assert_eq!(
+			read(json!({ "condition": "minecraft:random_chance_with_looting", "chance": 0.025 })),
+			Err(ConditionError::MissingField { condition: "minecraft:random_chance_with_looting".to_string(), field: "looting_multiplier".to_string() })
+		);
+		assert!(matches!(read(json!({ "condition": "minecraft:entity_properties", "entity": "player", "predicate": {} })), Err(ConditionError::InvalidField { .. })));
+		assert!(matches!(read(json!({ "condition": "minecraft:alternative", "terms": [{ "chance": 0.5 }] })), Err(ConditionError::Malformed(_))));
+		assert!(matches!(read(json!("minecraft:killed_by_player")), Err(ConditionError::Malformed(_))));
+	}
+
+	#[test]
+	fn keep_unknown_fields() {
+		let value = json!({ "condition": "minecraft:inverted", "term": { "condition": "minecraft:random_chance", "chance": 0.5, "chacne": 1 } });
+		let condition = Condition::from_value(&value).unwrap();
+
+		assert_eq!(condition, Condition::Inverted { term: Box::new(Condition::Other(value["term"].clone())) });
+		assert_eq!(condition.to_value(), value);
+		assert!(matches!(Condition::from_value(&json!({ "condition": "minecraft:random_chance", "chance": 0.5, "note": 1 })), Ok(Condition::Other(_))));
+		assert_eq!(
+			Condition::from_value(&json!({ "condition": "minecraft:random_chance", "chacne": 1 })),
+			Err(ConditionError::MissingField { condition: "minecraft:random_chance".to_string(), field: "chance".to_string() })
+		);
+		assert!(matches!(Condition::from_value(&json!({ "condition": "minecraft:random_chance", "chance": "high", "note": 1 })), Err(ConditionError::InvalidField { .. })));
+		assert!(matches!(
+			Condition::from_value(&json!({ "condition": "minecraft:inverted", "term": { "condition": "minecraft:random_chance" }, "note": 1 })),
+			Err(ConditionError::MissingField { .. })
+		));
+	}
+
+	#[test]
+	fn keep_unknown_conditions() {
+		let value = json!({ "condition": "boomber:moon_phase", "phase": 3 });
+		let condition = read(value.clone()).unwrap();
+
+		assert_eq!(condition, Condition::Other(value.clone()));
+		assert_eq!(condition.name(), Some("boomber:moon_phase"));
+		assert_eq!(condition.to_value(), value);
+	}
+
+	#[test]
+	fn write_conditions_like_vanilla() {
+		let value = json!({ "condition": "minecraft:location_check", "predicate": { "biome": "minecraft:jungle" }, "offsetY": -1 });
+		assert_eq!(read(value.clone()).unwrap().to_value(), value);
+
+		let value = json!({ "condition": "minecraft:random_chance_with_looting", "chance": 0.025, "looting_multiplier": 0.01 });
+		assert_eq!(serde_json::to_value(read(value.clone()).unwrap()).unwrap(), value);
+	}
+}
@@ -0,0 +1,67 @@
+use super::Namespace;
+use std::path::PathBuf;
+
+/// What `merge_with()` should do when two scripts define the same pool key with different drop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+	/// Stop merging with `MeguError::Conflict`
+	Error,
+	/// Keep the drop from the later script and report `Warning::Conflict`
+	#[default]
+	Warn,
+	/// Silently keep the drop from the earlier script
+	KeepFirst,
+	/// Silently keep the drop from the later script
+	KeepLast
+}
+
+impl std::str::FromStr for ConflictPolicy {
+	type Err = String;
+
+	/// Parse policy name in either `keep-first` or `keep_first` form
+	fn from_str(value: &str) -> Result<ConflictPolicy, String> {
+		match value {
+			"error" => Ok(ConflictPolicy::Error),
+			"warn" => Ok(ConflictPolicy::Warn),
+			"keep-first" | "keep_first" => Ok(ConflictPolicy::KeepFirst),
+			"keep-last" | "keep_last" => Ok(ConflictPolicy::KeepLast),
+			_ => Err(value.to_string())
+		}
+	}
+}
+
+/// A script that take part in merging
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptSource {
+	/// Position of the script in the slice given to `merge_with()`
+	pub index: usize,
+	/// Path the script was read from (if any)
+	pub path: Option<PathBuf>
+}
+
+/// Two scripts that define the same pool key with different drop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+	/// Pool key that both scripts define
+	pub key: Namespace,
+	/// Script that define `key` first
+	pub first: ScriptSource,
+	/// Script that define `key` again
+	pub second: ScriptSource
+}
+
+use std::fmt;
+use super::render::Paint;
+impl fmt::Display for ScriptSource {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match &self.path {
+			Some(path) => write!(f, "{}", path.display()),
+			None => write!(f, "script #{}", self.index)
+		}
+	}
+}
+impl fmt::Display for Conflict {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "'{}' is defined in both '{}' and '{}'.", self.key.to_string().cyan(), self.first.to_string().green(), self.second.to_string().green())
+	}
+}
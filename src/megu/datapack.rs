@@ -0,0 +1,464 @@
+use super::{MeguScript, MeguError, MergeOptions, ParseOptions, ConflictPolicy, Namespace, Extension, Resolver, Detector, Diagnostics, Warning, LootTable};
+use indexmap::IndexMap;
+use crate::util::{self, CompilerOptions, MetaError};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Datapack that contain loot table scripts.
+///
+/// Scripts are searched under `data/*/loot_tables/`, scripts that target the same loot table
+/// (i.e. `blocks/stone.ult` and `blocks/stone.json.merge`) are merged together in path order.
+///
+/// # Examples
+/// ```should_panic
+/// # use rna::datapack::Datapack;
+/// let datapack = Datapack::open("path/to/datapack").unwrap();
+///
+/// for (path, table) in datapack.compile("resource").unwrap() {
+///     table.write(datapack.root().join(path)).unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datapack {
+	root: PathBuf,
+	options: CompilerOptions,
+	targets: BTreeMap<PathBuf, Vec<PathBuf>>
+}
+
+impl Datapack {
+	/// Read `compiler_options` from `pack.mcmeta` and find every loot table script inside `root`.
+	///
+	/// # Errors
+	/// This method can fail when:
+	/// - `pack.mcmeta` is missing, doesn't have `compiler_options` field or contain invalid option
+	/// - I/O error occur while walking `data` directory
+	pub fn open(root: impl Into<PathBuf>) -> Result<Datapack, DatapackError> {
+		Datapack::open_with(root, &Detector::default())
+	}
+
+	/// Same as `open()` but use `detector` to find loot table scripts (i.e. `Resolver::detector()`)
+	pub fn open_with(root: impl Into<PathBuf>, detector: &Detector) -> Result<Datapack, DatapackError> {
+		let root: PathBuf = root.into();
+		let options = util::read_meta(root.join("pack.mcmeta"))?;
+
+		let data = root.join("data");
+		let mut targets: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::default();
+		for namespace in read_dir(&data)? {
+			let loot_tables = namespace.join("loot_tables");
+			if !loot_tables.is_dir() {
+				continue;
+			}
+
+			let mut scripts = Vec::default();
+			find_scripts(&loot_tables, detector, &mut scripts).map_err(|error| DatapackError::Io((loot_tables.clone(), error)))?;
+
+			for script in scripts {
+				let relative = script.strip_prefix(&root).unwrap_or(&script);
+				if let Some(target) = detector.strip(relative) {
+					// Append instead of `with_extension()` which would replace anything after a dot (i.e. `stone.v2`)
+					let mut target = target.into_os_string();
+					target.push(".json");
+					targets.entry(PathBuf::from(target)).or_default().push(script);
+				}
+			}
+		}
+
+		for scripts in targets.values_mut() {
+			scripts.sort();
+		}
+
+		Ok(Datapack { root, options, targets })
+	}
+
+	/// Root directory of this datapack
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Options from `compiler_options` field inside `pack.mcmeta`
+	pub fn options(&self) -> &CompilerOptions {
+		&self.options
+	}
+
+	/// Scripts grouped by the loot table they compile into.
+	///
+	/// Keys are relative to `root()` (i.e. `data/minecraft/loot_tables/blocks/stone.json`).
+	pub fn targets(&self) -> &BTreeMap<PathBuf, Vec<PathBuf>> {
+		&self.targets
+	}
+
+	/// Compile every loot table in this datapack with options from `pack.mcmeta`.
+	///
+	/// Warnings (i.e. `Warning::UnknownOption`) are discarded, `compile_with()` is the only way to get them.
+	pub fn compile(&self, resolver: impl Into<Resolver>) -> Result<BTreeMap<PathBuf, LootTable>, DatapackError> {
+		self.compile_with(resolver, &CompilerOptions::default(), &mut Diagnostics::default())
+	}
+
+	/// Compile every loot table in this datapack, any warning will be pushed into `diagnostics`.
+	///
+	/// Every option that is set in `options` override the one from `pack.mcmeta` (see `CompilerOptions::extend()`).
+	///
+	/// Return a map from output path (relative to `root()`) to compiled loot table.
+	///
+	/// # Errors
+	/// This method can fail when one of the scripts cannot be read or merged.
+	pub fn compile_with(&self, resolver: impl Into<Resolver>, options: &CompilerOptions, diagnostics: &mut Diagnostics) -> Result<BTreeMap<PathBuf, LootTable>, DatapackError> {
+		let result = merge_datapacks(std::slice::from_ref(self), resolver, options, diagnostics)?;
+		Ok(result.into_iter().map(|(path, merged)| (path, merged.table)).collect())
+	}
+}
+
+/// Loot table merged from one or more datapacks
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedTable {
+	/// Compiled loot table
+	pub table: LootTable,
+	/// Where each pool come from, in the same order as `table.pools`
+	pub provenance: IndexMap<Namespace, Provenance>
+}
+
+/// Script that supplied a pool in `MergedTable`
+///
+/// When several scripts supply the same drop, the first one is recorded.
+/// Pool that `script` inherit through its `extend` field (instead of defining it) has `extend` set,
+/// so two packs that extend the same table don't look like the first pack define the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+	/// Position of the datapack in the slice given to `merge_datapacks()`
+	pub pack: usize,
+	/// Root directory of the datapack
+	pub root: PathBuf,
+	/// Script that define the pool
+	pub script: PathBuf,
+	/// Table in the `extend` field of `script` that the pool come from, `None` when `script` define it
+	pub extend: Option<Extension>
+}
+
+/// Merge loot table scripts from several datapacks, any warning will be pushed into `diagnostics`.
+///
+/// Every option that is set in `options` override the one from `Datapack::options()` of each pack.
+/// Scripts (and their `extend` chain) are parsed with the options of their own pack.
+/// Each loot table is merged with the strictest options among the packs that contain it:
+/// `error` is stricter than `warn`, and packs that set both `keep_first` and `keep_last` get `warn` since only one can win.
+/// Pools are sorted when any of these packs set `sort_pools`.
+///
+/// Scripts that target the same loot table are merged in pack order, then in path order inside each pack.
+/// With the default `ConflictPolicy::Warn`, later pack win when two packs define the same pool key and `Warning::Conflict` is reported.
+///
+/// # Examples
+/// ```should_panic
+/// # use rna::datapack::{self, Datapack};
+/// # use rna::diagnostic::Diagnostics;
+/// # use rna::utils::CompilerOptions;
+/// let packs = [Datapack::open("ruby").unwrap(), Datapack::open("draconic_ore").unwrap()];
+/// let tables = datapack::merge_datapacks(&packs, "resource", &CompilerOptions::default(), &mut Diagnostics::default()).unwrap();
+///
+/// let stone = &tables[std::path::Path::new("data/minecraft/loot_tables/blocks/stone.json")];
+/// for (key, provenance) in &stone.provenance {
+///     println!("{} come from {}", key, provenance.root.display());
+/// }
+/// ```
+///
+/// # Errors
+/// This method can fail when one of the scripts cannot be read or merged.
+pub fn merge_datapacks(packs: &[Datapack], resolver: impl Into<Resolver>, options: &CompilerOptions, diagnostics: &mut Diagnostics) -> Result<BTreeMap<PathBuf, MergedTable>, DatapackError> {
+	let resolver = resolver.into();
+	let caller = options;
+	let options: Vec<CompilerOptions> = packs
+		.iter()
+		.map(|datapack| {
+			let mut result = datapack.options.clone();
+			result.extend(caller);
+			result
+		})
+		.collect();
+
+	let mut targets: BTreeMap<&PathBuf, Vec<(usize, &PathBuf)>> = BTreeMap::default();
+	for (pack, datapack) in packs.iter().enumerate() {
+		for name in &datapack.options.unknown {
+			diagnostics.push(Warning::UnknownOption { name: name.clone(), path: datapack.root.join("pack.mcmeta") });
+		}
+
+		for (target, scripts) in &datapack.targets {
+			targets.entry(target).or_default().extend(scripts.iter().map(|script| (pack, script)));
+		}
+	}
+
+	let mut result = BTreeMap::default();
+	for (target, paths) in targets {
+		let parse_options: Vec<ParseOptions> = paths.iter().map(|&(pack, _)| options[pack].parse_options()).collect();
+		let scripts = paths
+			.iter()
+			.zip(&parse_options)
+			.map(|(&(_, path), &parse_options)| super::interpret_file_with(path, &resolver, parse_options))
+			.collect::<Result<Vec<MeguScript>, MeguError>>()?;
+
+		let merge = table_options(caller, paths.iter().map(|&(pack, _)| &packs[pack].options));
+		let (script, owners) = super::merge_tracked(&scripts, &parse_options, &resolver, &merge, diagnostics)?;
+		let provenance = script.pools
+			.keys()
+			.map(|key| {
+				let owner = &scripts[owners[key]];
+				let extend = owner.extend.clone().filter(|_| !owner.pools.contains_key(key));
+				let (pack, path) = paths[owners[key]];
+				(key.clone(), Provenance { pack, root: packs[pack].root.clone(), script: path.clone(), extend })
+			})
+			.collect();
+
+		result.insert(target.clone(), MergedTable { table: LootTable::from(&script), provenance });
+	}
+
+	Ok(result)
+}
+
+/// Options to merge a loot table with, from the options of every pack that contain it (see `merge_datapacks()`)
+fn table_options<'a>(caller: &CompilerOptions, packs: impl Iterator<Item = &'a CompilerOptions>) -> MergeOptions {
+	let mut result = CompilerOptions::default();
+	for pack in packs {
+		result.conflict_policy = match (result.conflict_policy, pack.conflict_policy) {
+			(Some(current), Some(policy)) => Some(strictest(current, policy)),
+			(current, policy) => current.or(policy)
+		};
+		result.sort_pools = match (result.sort_pools, pack.sort_pools) {
+			(Some(current), Some(sort)) => Some(current || sort),
+			(current, sort) => current.or(sort)
+		};
+	}
+
+	result.extend(caller);
+	result.merge_options()
+}
+
+/// Stricter of the two policies, silently keeping one side only happen when both agree on which side
+fn strictest(first: ConflictPolicy, second: ConflictPolicy) -> ConflictPolicy {
+	match (first, second) {
+		(ConflictPolicy::Error, _) | (_, ConflictPolicy::Error) => ConflictPolicy::Error,
+		(first, second) if first == second => first,
+		_ => ConflictPolicy::Warn
+	}
+}
+
+fn read_dir(directory: &Path) -> Result<Vec<PathBuf>, DatapackError> {
+	let error = |error| DatapackError::Io((directory.to_path_buf(), error));
+	let mut result = Vec::default();
+	for entry in fs::read_dir(directory).map_err(error)? {
+		result.push(entry.map_err(error)?.path());
+	}
+
+	Ok(result)
+}
+
+/// Find every loot table script under `directory`
+fn find_scripts(directory: &Path, detector: &Detector, result: &mut Vec<PathBuf>) -> io::Result<()> {
+	for entry in fs::read_dir(directory)? {
+		let path = entry?.path();
+		if path.is_dir() {
+			find_scripts(&path, detector, result)?;
+		}
+		else if detector.detect(&path).is_some() {
+			result.push(path);
+		}
+	}
+
+	Ok(())
+}
+
+/// General error type for `Datapack`
+#[derive(Debug)]
+pub enum DatapackError {
+	/// Emit when `pack.mcmeta` is invalid
+	Meta(MetaError),
+	/// Emit when I/O error occur while walking the datapack
+	Io((PathBuf, io::Error)),
+	/// Emit when one of the scripts cannot be compiled
+	Megu(MeguError)
+}
+
+impl From<MetaError> for DatapackError {
+	fn from(error: MetaError) -> DatapackError {
+		DatapackError::Meta(error)
+	}
+}
+impl From<MeguError> for DatapackError {
+	fn from(error: MeguError) -> DatapackError {
+		DatapackError::Megu(error)
+	}
+}
+
+use std::fmt;
+use super::render::Paint;
+impl std::error::Error for DatapackError {}
+impl fmt::Display for DatapackError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DatapackError::Meta(error) => write!(f, "{}", error),
+			DatapackError::Io((path, error)) => write!(f, "[{}] {}", path.display().to_string().green(), error),
+			DatapackError::Megu(error) => write!(f, "{}", error)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::conflict::ConflictPolicy;
+	use crate::megu::temp::TempDir;
+
+	fn create_datapack(name: &str, files: &[(&str, &str)]) -> TempDir {
+		let root = TempDir::new(name);
+		for (file, content) in files {
+			root.write(file, content);
+		}
+		root
+	}
+
+	#[test]
+	fn merge_scripts_with_same_target() {
+		let root = create_datapack("merge", &[
+			("pack.mcmeta", r#"{"pack":{"pack_format":7,"description":""},"compiler_options":[]}"#),
+			("data/minecraft/loot_tables/blocks/stone.ult", r#"{"type":"minecraft:block","pools":{"boomber:ruby":{"type":"minecraft:item","name":"boomber:ruby"}}}"#),
+			("data/minecraft/loot_tables/blocks/stone.megu", r#"{"pools":{"boomber:sapphire":{"type":"minecraft:item","name":"boomber:sapphire"}}}"#),
+			("data/minecraft/loot_tables/blocks/stone.json.merge", r#"{"pools":{"boomber:emerald":{"type":"minecraft:item","name":"boomber:emerald"}}}"#),
+			("data/boomber/loot_tables/ruby_ore.ult", r#"{"pools":{}}"#),
+			("data/boomber/loot_tables/ruby_ore.v2.ult", r#"{"pools":{}}"#),
+			("data/boomber/functions/tick.mcfunction", "")
+		]);
+
+		let datapack = Datapack::open(root.path()).unwrap();
+		let stone = PathBuf::from("data/minecraft/loot_tables/blocks/stone.json");
+		assert_eq!(datapack.targets().keys().collect::<Vec<_>>(), [
+			&PathBuf::from("data/boomber/loot_tables/ruby_ore.json"),
+			&PathBuf::from("data/boomber/loot_tables/ruby_ore.v2.json"),
+			&stone
+		]);
+		assert_eq!(datapack.targets()[&stone].len(), 3);
+
+		let tables = datapack.compile("resource").unwrap();
+		assert_eq!(tables[&stone].kind.as_deref(), Some("minecraft:block"));
+		assert_eq!(tables[&stone].pools.len(), 3);
+	}
+
+	#[test]
+	fn later_pack_win_and_record_provenance() {
+		let meta = ("pack.mcmeta", r#"{"pack":{"pack_format":7,"description":""},"compiler_options":[]}"#);
+		let ruby = create_datapack("ruby", &[
+			meta,
+			("data/minecraft/loot_tables/blocks/stone.ult", r#"{"pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:ruby"},"boomber:ruby":{"type":"minecraft:item","name":"boomber:ruby"}}}"#)
+		]);
+		let draconic = create_datapack("draconic", &[
+			meta,
+			("data/minecraft/loot_tables/blocks/stone.megu", r#"{"pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:draconic_ore"}}}"#)
+		]);
+
+		let packs = [Datapack::open(ruby.path()).unwrap(), Datapack::open(draconic.path()).unwrap()];
+		let tables = merge_datapacks(&packs, "resource", &CompilerOptions::default(), &mut Diagnostics::default()).unwrap();
+		let stone = &tables[&PathBuf::from("data/minecraft/loot_tables/blocks/stone.json")];
+
+		assert_eq!(stone.table.pools[0].entries[0].name.as_deref(), Some("boomber:draconic_ore"));
+		assert_eq!(stone.provenance[&Namespace::new("boomber", "gem")].pack, 1);
+		assert_eq!(stone.provenance[&Namespace::new("boomber", "gem")].root, draconic.path());
+		assert_eq!(stone.provenance[&Namespace::new("boomber", "ruby")].script, ruby.join("data/minecraft/loot_tables/blocks/stone.ult"));
+	}
+
+	#[test]
+	fn record_extended_table_of_inherited_pool() {
+		let meta = ("pack.mcmeta", r#"{"compiler_options":[]}"#);
+		let base = TempDir::new("base");
+		base.write("boomber/base.ult", r#"{"pools":{"boomber:coal":{"type":"minecraft:item","name":"minecraft:coal"}}}"#);
+
+		let script = ("data/minecraft/loot_tables/blocks/stone.ult", r#"{"extend":"boomber:base","pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:ruby"}}}"#);
+		let ruby = create_datapack("ruby", &[meta, script]);
+		let sapphire = create_datapack("sapphire", &[meta, script]);
+
+		let packs = [Datapack::open(ruby.path()).unwrap(), Datapack::open(sapphire.path()).unwrap()];
+		let tables = merge_datapacks(&packs, base.path(), &CompilerOptions::default(), &mut Diagnostics::default()).unwrap();
+		let stone = &tables[&PathBuf::from("data/minecraft/loot_tables/blocks/stone.json")];
+
+		let coal = &stone.provenance[&Namespace::new("boomber", "coal")];
+		assert_eq!(coal.extend.as_ref().map(Extension::location), Some(base.join("boomber/base.ult").as_path()));
+		assert_eq!(stone.provenance[&Namespace::new("boomber", "gem")].extend, None);
+	}
+
+	#[test]
+	fn honour_compiler_options() {
+		let root = create_datapack("options", &[
+			("pack.mcmeta", r#"{"compiler_options":[{"name":"conflict_policy","value":"error"},{"name":"minify"}]}"#),
+			("data/minecraft/loot_tables/blocks/stone.megu", r#"{"pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:ruby"}}}"#),
+			("data/minecraft/loot_tables/blocks/stone.ult", r#"{"pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:sapphire"}}}"#)
+		]);
+
+		let datapack = Datapack::open(root.path()).unwrap();
+		assert!(matches!(datapack.compile("resource"), Err(DatapackError::Megu(MeguError::Conflict(_)))));
+
+		let mut diagnostics = Diagnostics::default();
+		let keep_last = CompilerOptions { conflict_policy: Some(ConflictPolicy::KeepLast), ..CompilerOptions::default() };
+		datapack.compile_with("resource", &keep_last, &mut diagnostics).unwrap();
+		assert_eq!(diagnostics.warnings(), [Warning::UnknownOption { name: "minify".to_string(), path: root.join("pack.mcmeta") }]);
+	}
+
+	#[test]
+	fn parse_each_pack_with_its_own_options() {
+		let script = ("data/minecraft/loot_tables/blocks/stone.ult", r#"{"pools":{"boomber:gem":{"type":"minecraft:item","nmae":"boomber:ruby"}}}"#);
+		let lenient = create_datapack("lenient", &[("pack.mcmeta", r#"{"compiler_options":[]}"#), script]);
+		let strict = create_datapack("strict", &[("pack.mcmeta", r#"{"compiler_options":[{"name":"strict"}]}"#), script]);
+
+		let packs = [Datapack::open(lenient.path()).unwrap(), Datapack::open(strict.path()).unwrap()];
+		let result = merge_datapacks(&packs, "resource", &CompilerOptions::default(), &mut Diagnostics::default());
+		assert!(matches!(result, Err(DatapackError::Megu(MeguError::Read((path, _)))) if path.starts_with(strict.path())));
+
+		let lenient_only = CompilerOptions { strict: Some(false), ..CompilerOptions::default() };
+		assert!(merge_datapacks(&packs, "resource", &lenient_only, &mut Diagnostics::default()).is_ok());
+	}
+
+	#[test]
+	fn merge_with_strictest_options_of_every_pack() {
+		let pack = |name: &str, options: &str, gem: &str| create_datapack(name, &[
+			("pack.mcmeta", format!(r#"{{"compiler_options":{}}}"#, options).as_str()),
+			("data/minecraft/loot_tables/blocks/stone.ult", format!(r#"{{"pools":{{"boomber:gem":{{"type":"minecraft:item","name":"{}"}}}}}}"#, gem).as_str())
+		]);
+		let strict = pack("error", r#"[{"name":"conflict_policy","value":"error"}]"#, "boomber:ruby");
+		let keep_first = pack("keep-first", r#"[{"name":"conflict_policy","value":"keep_first"}]"#, "boomber:sapphire");
+		let keep_last = pack("keep-last", r#"[{"name":"conflict_policy","value":"keep_last"}]"#, "boomber:emerald");
+
+		let open = |roots: &[&TempDir]| roots.iter().map(|root| Datapack::open(root.path()).unwrap()).collect::<Vec<_>>();
+		let result = merge_datapacks(&open(&[&strict, &keep_last]), "resource", &CompilerOptions::default(), &mut Diagnostics::default());
+		assert!(matches!(result, Err(DatapackError::Megu(MeguError::Conflict(_)))));
+
+		let mut diagnostics = Diagnostics::default();
+		merge_datapacks(&open(&[&keep_first, &keep_last]), "resource", &CompilerOptions::default(), &mut diagnostics).unwrap();
+		assert!(matches!(diagnostics.warnings(), [Warning::Conflict(_)]));
+
+		let keep_last_only = CompilerOptions { conflict_policy: Some(ConflictPolicy::KeepLast), ..CompilerOptions::default() };
+		assert!(merge_datapacks(&open(&[&strict, &keep_last]), "resource", &keep_last_only, &mut Diagnostics::default()).is_ok());
+	}
+
+	#[test]
+	fn parse_extend_chain_with_pack_options() {
+		let base = TempDir::new("strict-base");
+		base.write("boomber/base.ult", r#"{"pools":{"boomber:coal":{"type":"minecraft:item","nmae":"minecraft:coal"}}}"#);
+		let root = create_datapack("strict-extend", &[
+			("pack.mcmeta", r#"{"compiler_options":[{"name":"strict"}]}"#),
+			("data/minecraft/loot_tables/blocks/stone.ult", r#"{"extend":"boomber:base","pools":{}}"#)
+		]);
+
+		let datapack = Datapack::open(root.path()).unwrap();
+		let result = datapack.compile(base.path());
+		assert!(matches!(result, Err(DatapackError::Megu(MeguError::Merge((Some(path), _)))) if path == base.join("boomber/base.ult")));
+
+		let lenient = CompilerOptions { strict: Some(false), ..CompilerOptions::default() };
+		assert!(datapack.compile_with(base.path(), &lenient, &mut Diagnostics::default()).is_ok());
+	}
+
+	#[test]
+	fn reject_invalid_compiler_option() {
+		let root = create_datapack("invalid", &[("pack.mcmeta", r#"{"compiler_options":[{"name":"strict","value":"yes"}]}"#)]);
+		assert!(matches!(Datapack::open(root.path()), Err(DatapackError::Meta(MetaError::InvalidOption(_)))));
+	}
+
+	#[test]
+	fn require_compiler_options() {
+		let root = create_datapack("meta", &[("pack.mcmeta", r#"{"pack":{"pack_format":7,"description":""}}"#)]);
+		assert!(matches!(Datapack::open(root.path()), Err(DatapackError::Meta(MetaError::NoCompilerOptions(_)))));
+	}
+}
@@ -0,0 +1,264 @@
+use super::{Namespace, MeguDrop, DropType, DropFormat, LootTable, NumberProvider, ReadError};
+use super::loot_table::{LootPool, LootEntry};
+use super::script::ScriptFormat;
+use std::collections::HashSet;
+use indexmap::IndexMap;
+
+/// Convert vanilla loot table into `ScriptFormat`.
+///
+/// `location` is the loot table's own namespace (i.e. `minecraft:entities/zombie`) and is used to derive each pool key.
+///
+/// Drops keep the order of pools and entries in `table`.
+/// Pool with a single entry become a drop of that entry, pool's `conditions` and `functions` are appended to the entry's
+/// and pool's `rolls` and `bonus_rolls` are copied into it.
+/// Pool with more than one entry become a single `rna:pool` drop with the entries as `children`,
+/// so only one of them is still picked on each roll. Empty pools are skipped.
+/// Composite entries (`alternatives`, `group` and `sequence`) keep their `children` and are marked as `unsafe`.
+///
+/// # Pool keys
+/// Pool keys follow `{prefix}:{prefix}/{suffix}/{name}` where `name` is the (first) entry's name without its namespace prefix.
+/// `/{name}` is omitted when the loot table's path already end with it and repeated keys get `/1`, `/2`, ... appended.
+///
+/// # Examples
+/// ```
+/// # use rna::{LootTable, Namespace};
+/// # use rna::decompile::decompile;
+/// let table: LootTable = serde_json::from_str(r#"{
+///     "type": "minecraft:block",
+///     "pools": [{ "rolls": 1, "entries": [{ "type": "minecraft:item", "name": "minecraft:gold_ore" }] }]
+/// }"#).unwrap();
+///
+/// let script = decompile(&table, &Namespace::new("minecraft", "blocks/gold_ore"));
+/// assert!(script.pools.contains_key("minecraft:minecraft/blocks/gold_ore"));
+/// ```
+pub fn decompile(table: &LootTable, location: &Namespace) -> ScriptFormat {
+	let mut pools = IndexMap::default();
+	let mut used = HashSet::new();
+
+	for pool in &table.pools {
+		let drop = match pool.entries.as_slice() {
+			[] => continue,
+			[entry] => drop_format(entry, pool),
+			_ => pool_format(pool)
+		};
+		let key = pool_key(location, entry_leaf(&pool.entries[0]), &mut used);
+		pools.insert(key, drop);
+	}
+
+	ScriptFormat {
+		kind: table.kind.clone(),
+		extend: None,
+		pools,
+		remove: None
+	}
+}
+
+/// Read vanilla loot table from `path` and convert it into `ScriptFormat`.
+/// 
+/// Use `location_of()` to derive `location` from a path inside datapack.
+///
+/// # Errors
+/// This method can fail when:
+/// - I/O emit error (i.e. Cannot read file from path)
+/// - Serde emit Error (i.e. JSON Error)
+pub fn decompile_file(path: impl Into<PathBuf>, location: &Namespace) -> Result<ScriptFormat, ReadError> {
+	let path = path.into();
+	let content = fs::read(path)?;
+	let table: LootTable = js::from_slice(&content)?;
+
+	Ok(decompile(&table, location))
+}
+
+/// Derive loot table's namespace from a path in datapack.
+///
+/// # Examples
+/// ```
+/// # use rna::Namespace;
+/// # use rna::decompile::location_of;
+/// assert_eq!(
+///     location_of("data/minecraft/loot_tables/entities/zombie.json"),
+///     Some(Namespace::new("minecraft", "entities/zombie"))
+/// );
+/// assert_eq!(location_of("data/minecraft/functions/tick.mcfunction"), None);
+/// ```
+pub fn location_of(path: impl AsRef<Path>) -> Option<Namespace> {
+	let components: Vec<&str> = path.as_ref()
+		.components()
+		.filter_map(|component| component.as_os_str().to_str())
+		.collect();
+
+	let index = components.iter().rposition(|&component| component == "loot_tables")?;
+	if index == 0 || index + 1 >= components.len() {
+		return None;
+	}
+
+	let prefix = components[index - 1];
+	let mut suffix = components[index + 1..].join("/");
+	if let Some(position) = suffix.rfind('.') {
+		suffix.truncate(position);
+	}
+
+	Some(Namespace::new(prefix, suffix))
+}
+
+/// Get the last part of pool key from entry's name or its first named child
+fn entry_leaf(entry: &LootEntry) -> &str {
+	let name = match &entry.name {
+		Some(name) => Some(name.as_str()),
+		None => entry.children.iter().map(entry_leaf).next()
+	};
+	let name = name.unwrap_or(&entry.kind);
+
+	match name.rfind(':') {
+		Some(position) => &name[position + 1..],
+		None => name
+	}
+}
+
+fn pool_key(location: &Namespace, leaf: &str, used: &mut HashSet<String>) -> String {
+	let base = format!("{}/{}", location.prefix, location.suffix);
+	let path = if base.ends_with(&format!("/{}", leaf)) {
+		base
+	}
+	else {
+		format!("{}/{}", base, leaf)
+	};
+
+	let mut key = format!("{}:{}", location.prefix, path);
+	let mut counter = 0;
+	while used.contains(&key) {
+		counter += 1;
+		key = format!("{}:{}/{}", location.prefix, path, counter);
+	}
+
+	used.insert(key.clone());
+	key
+}
+
+fn drop_format(entry: &LootEntry, pool: &LootPool) -> DropFormat {
+	let mut result = child_format(entry);
+	if let Some(conditions) = &mut result.conditions {
+		conditions.extend(pool.conditions.iter().cloned());
+	}
+	if let Some(functions) = &mut result.functions {
+		functions.extend(pool.functions.iter().cloned());
+	}
+	result.rolls = Some(pool.rolls.to_value());
+	result.bonus_rolls = pool.bonus_rolls.as_ref().map(NumberProvider::to_value);
+
+	result
+}
+
+fn pool_format(pool: &LootPool) -> DropFormat {
+	DropFormat {
+		r#unsafe: Some(true),
+		r#type: DropType::Pool.name().to_string(),
+		name: None,
+		children: Some(pool.entries.iter().map(child_format).collect()),
+		functions: Some(pool.functions.clone()),
+		conditions: Some(pool.conditions.clone()),
+		rolls: Some(pool.rolls.to_value()),
+		bonus_rolls: pool.bonus_rolls.as_ref().map(NumberProvider::to_value),
+		weight: None,
+		quality: None
+	}
+}
+
+fn child_format(entry: &LootEntry) -> DropFormat {
+	let r#unsafe = DropType::get_drop_type(&entry.kind)
+		.map(MeguDrop::is_unsafe)
+		.unwrap_or_default();
+	let children = if entry.children.is_empty() {
+		None
+	}
+	else {
+		Some(entry.children.iter().map(child_format).collect())
+	};
+
+	DropFormat {
+		r#unsafe: Some(r#unsafe),
+		r#type: entry.kind.clone(),
+		name: entry.name.clone(),
+		children,
+		functions: Some(entry.functions.clone()),
+		conditions: Some(entry.conditions.clone()),
+		rolls: None,
+		bonus_rolls: None,
+		weight: entry.weight,
+		quality: entry.quality
+	}
+}
+
+use std::path::{Path, PathBuf};
+use std::fs;
+use serde_json as js;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::MeguScript;
+
+	const ZOMBIE: &str = r#"
+	{
+		"type": "minecraft:entity",
+		"pools": [
+			{
+				"rolls": 1,
+				"entries": [
+					{
+						"type": "minecraft:item",
+						"functions": [
+							{ "function": "minecraft:set_count", "count": { "min": 0, "max": 2, "type": "minecraft:uniform" } },
+							{ "function": "minecraft:looting_enchant", "count": { "min": 0, "max": 1 } }
+						],
+						"name": "minecraft:rotten_flesh"
+					}
+				]
+			},
+			{
+				"rolls": 1,
+				"entries": [
+					{ "type": "minecraft:item", "name": "minecraft:iron_ingot" },
+					{ "type": "minecraft:item", "name": "minecraft:carrot" },
+					{ "type": "minecraft:item", "name": "minecraft:potato" }
+				],
+				"conditions": [
+					{ "condition": "minecraft:killed_by_player" },
+					{ "condition": "minecraft:random_chance_with_looting", "chance": 0.025, "looting_multiplier": 0.01 }
+				]
+			}
+		]
+	}
+	"#;
+
+	/// `minecraft/entities/zombie.ult` from `precompiled_vanilla_loot_table.tar.gz`, except the iron ingot, carrot and potato pool
+	/// is kept together instead of split into three drops
+	const ZOMBIE_SCRIPT: &str = r#"{"type":"minecraft:entity","pools":{"minecraft:minecraft/entities/zombie/rotten_flesh":{"unsafe":false,"type":"minecraft:item","name":"minecraft:rotten_flesh","conditions":[],"functions":[{"function":"minecraft:set_count","count":{"min":0,"max":2,"type":"minecraft:uniform"}},{"function":"minecraft:looting_enchant","count":{"min":0,"max":1}}],"rolls":1},"minecraft:minecraft/entities/zombie/iron_ingot":{"unsafe":true,"type":"rna:pool","children":[{"unsafe":false,"type":"minecraft:item","name":"minecraft:iron_ingot","conditions":[],"functions":[]},{"unsafe":false,"type":"minecraft:item","name":"minecraft:carrot","conditions":[],"functions":[]},{"unsafe":false,"type":"minecraft:item","name":"minecraft:potato","conditions":[],"functions":[]}],"conditions":[{"condition":"minecraft:killed_by_player"},{"condition":"minecraft:random_chance_with_looting","chance":0.025,"looting_multiplier":0.01}],"functions":[],"rolls":1}}}"#;
+
+	#[test]
+	fn decompile_matches_precompiled_script() {
+		let table: LootTable = js::from_str(ZOMBIE).unwrap();
+		let value = decompile(&table, &Namespace::new("minecraft", "entities/zombie"));
+		let expect: ScriptFormat = js::from_str(ZOMBIE_SCRIPT).unwrap();
+
+		assert_eq!(value, expect);
+	}
+
+	#[test]
+	fn compile_decompiled_multi_entry_pool() {
+		let table: LootTable = js::from_str(ZOMBIE).unwrap();
+		let script = MeguScript::from(decompile(&table, &Namespace::new("minecraft", "entities/zombie")));
+
+		assert_eq!(LootTable::from(&script), table);
+	}
+
+	#[test]
+	fn repeated_keys_get_numbered() {
+		let location = Namespace::new("minecraft", "blocks/snow");
+		let mut used = HashSet::new();
+
+		assert_eq!(pool_key(&location, "snowball", &mut used), "minecraft:minecraft/blocks/snow/snowball");
+		assert_eq!(pool_key(&location, "snowball", &mut used), "minecraft:minecraft/blocks/snow/snowball/1");
+		assert_eq!(pool_key(&location, "snow", &mut used), "minecraft:minecraft/blocks/snow");
+	}
+}
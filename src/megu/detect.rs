@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+
+/// Flavour of loot table script, detected from its file extension
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd)]
+pub enum ScriptKind {
+	/// `.ult` file
+	Ult,
+	/// `.megu` file
+	Megu,
+	/// `.json.merge` file
+	JsonMerge,
+	/// Extension registered with `Detector::extension()`
+	Custom(String)
+}
+
+impl ScriptKind {
+	/// File extension of this flavour without leading dot (i.e. `json.merge`)
+	pub fn extension(&self) -> &str {
+		match self {
+			ScriptKind::Ult => "ult",
+			ScriptKind::Megu => "megu",
+			ScriptKind::JsonMerge => "json.merge",
+			ScriptKind::Custom(extension) => extension
+		}
+	}
+}
+
+const BUILTIN: [ScriptKind; 3] = [ScriptKind::Ult, ScriptKind::Megu, ScriptKind::JsonMerge];
+
+/// Detect loot table script from file name.
+///
+/// Compound extensions like `.json.merge` are matched against the whole file name,
+/// when more than one extension match, the longest one win.
+///
+/// # Examples
+/// ```
+/// # use rna::detect::{Detector, ScriptKind};
+/// let detector = Detector::new().extension("loot.patch");
+///
+/// assert_eq!(detector.detect("blocks/stone.json.merge"), Some(ScriptKind::JsonMerge));
+/// assert_eq!(detector.detect("blocks/stone.loot.patch"), Some(ScriptKind::Custom("loot.patch".to_string())));
+/// assert_eq!(detector.detect("blocks/stone.json"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Detector {
+	extra: Vec<String>
+}
+
+impl Detector {
+	/// Create detector that only know `.ult`, `.megu` and `.json.merge`
+	pub fn new() -> Detector {
+		Detector::default()
+	}
+
+	/// Register extra extension, leading dot is optional
+	pub fn extension(mut self, extension: impl Into<String>) -> Detector {
+		self.register(extension);
+		self
+	}
+
+	/// Register extra extension, leading dot is optional
+	pub fn register(&mut self, extension: impl Into<String>) {
+		let extension: String = extension.into();
+		let extension = extension.trim_start_matches('.').to_string();
+
+		if !extension.is_empty() && !self.kinds().any(|kind| kind.extension() == extension) {
+			self.extra.push(extension);
+		}
+	}
+
+	/// Every known flavour, built-in flavours come first
+	pub fn kinds(&self) -> impl Iterator<Item = ScriptKind> + '_ {
+		BUILTIN.iter().cloned().chain(self.extra.iter().cloned().map(ScriptKind::Custom))
+	}
+
+	/// Find which flavour `path` is, return `None` when it's not a loot table script
+	pub fn detect(&self, path: impl AsRef<Path>) -> Option<ScriptKind> {
+		let name = path.as_ref().file_name()?.to_str()?;
+
+		self.kinds()
+			.filter(|kind| {
+				let extension = kind.extension();
+				name.len() > extension.len() + 1
+					&& name.ends_with(extension)
+					&& name[..name.len() - extension.len()].ends_with('.')
+			})
+			.max_by_key(|kind| kind.extension().len())
+	}
+
+	/// Remove script extension from `path` (i.e. `stone.json.merge` become `stone`)
+	///
+	/// Return `None` when `path` is not a loot table script.
+	pub fn strip(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+		let path = path.as_ref();
+		let kind = self.detect(path)?;
+		let name = path.file_name()?.to_str()?;
+
+		Some(path.with_file_name(&name[..name.len() - kind.extension().len() - 1]))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn detect_compound_extension() {
+		let detector = Detector::new();
+		assert_eq!(detector.detect("stone.ult"), Some(ScriptKind::Ult));
+		assert_eq!(detector.detect("stone.megu"), Some(ScriptKind::Megu));
+		assert_eq!(detector.detect("data/stone.json.merge"), Some(ScriptKind::JsonMerge));
+		assert_eq!(detector.detect("stone.merge"), None);
+		assert_eq!(detector.detect(".ult"), None);
+		assert_eq!(detector.detect("ult"), None);
+	}
+
+	#[test]
+	fn longest_extension_win() {
+		let detector = Detector::new().extension(".merge").extension("merge").extension("ult");
+		assert_eq!(detector.kinds().count(), 4);
+		assert_eq!(detector.detect("stone.json.merge"), Some(ScriptKind::JsonMerge));
+		assert_eq!(detector.detect("stone.merge"), Some(ScriptKind::Custom("merge".to_string())));
+	}
+
+	#[test]
+	fn strip_script_extension() {
+		let detector = Detector::new();
+		assert_eq!(detector.strip("blocks/stone.json.merge"), Some(PathBuf::from("blocks/stone")));
+		assert_eq!(detector.strip("blocks/stone.ult"), Some(PathBuf::from("blocks/stone")));
+		assert_eq!(detector.strip("blocks/stone.json"), None);
+	}
+}
@@ -0,0 +1,432 @@
+use super::conflict::{Conflict, ScriptSource};
+use super::pattern::Pattern;
+use super::span::Span;
+use super::code;
+use super::{MeguError, ReadError, Extension, ExtensionError, DecodeError};
+use super::script::ScriptFormatError;
+use super::drop::{DropType, DropTypeError};
+use super::datapack::DatapackError;
+use super::condition::ConditionError;
+use super::function::FunctionError;
+use crate::util::MetaError;
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+
+/// Problem that doesn't stop compilation but should be reported to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+	/// Two scripts define the same pool key, emit with `ConflictPolicy::Warn`
+	Conflict(Conflict),
+	/// Pattern in `remove` field doesn't match any drop (i.e. the drop was renamed in a newer version)
+	UnmatchedRemove {
+		pattern: Pattern,
+		/// Script that was merged
+		script: ScriptSource,
+		/// Script in the `extend` chain of `script` that declared `pattern`, `None` when `script` declared it
+		extend: Option<Extension>
+	},
+	/// `compiler_options` field inside `pack.mcmeta` contain option that this library doesn't know
+	UnknownOption {
+		name: String,
+		path: PathBuf
+	}
+}
+
+/// Collection of warnings emit alongside `MeguResult`
+///
+/// # Examples
+/// ```
+/// # use rna::diagnostic::Diagnostics;
+/// let diagnostics = Diagnostics::default();
+/// assert!(diagnostics.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Diagnostics {
+	warnings: Vec<Warning>
+}
+
+impl Diagnostics {
+	/// Add `warning` to this collection
+	pub fn push(&mut self, warning: Warning) {
+		self.warnings.push(warning);
+	}
+
+	/// Every warning in the order they were emitted
+	pub fn warnings(&self) -> &[Warning] {
+		&self.warnings
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.warnings.is_empty()
+	}
+
+	pub fn len(&self) -> usize {
+		self.warnings.len()
+	}
+}
+
+impl IntoIterator for Diagnostics {
+	type Item = Warning;
+	type IntoIter = std::vec::IntoIter<Warning>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.warnings.into_iter()
+	}
+}
+
+use std::fmt;
+use super::render::{Paint, plain};
+impl fmt::Display for Warning {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Warning::Conflict(conflict) => write!(f, "{}", conflict),
+			Warning::UnmatchedRemove { pattern, script, extend: None } => write!(f, "[{}] '{}' in {} field does not match any drop.", script.to_string().green(), pattern.to_string().cyan(), "remove".black().on_white()),
+			Warning::UnmatchedRemove { pattern, script, extend: Some(extension) } => write!(
+				f,
+				"[{}] '{}' in {} field does not match any drop, inherited by '{}'.",
+				extension.location().display().to_string().green(),
+				pattern.to_string().cyan(),
+				"remove".black().on_white(),
+				script.to_string().green()
+			),
+			Warning::UnknownOption { name, path } => write!(f, "[{}] Unknown compiler option '{}'.", path.display().to_string().green(), name.cyan())
+		}
+	}
+}
+
+/// How bad a `Diagnostic` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+	Error,
+	Warning
+}
+
+/// Structured form of an error or a warning for tools like CI bot and editor plugin.
+///
+/// `message` never contain terminal colors, `file` and `span` are kept in their own fields.
+///
+/// # Examples
+/// ```
+/// # use rna::diagnostic::{ToDiagnostics, Severity};
+/// # use rna::error::DropTypeError;
+/// let error = DropTypeError::InvalidType("minecraft:itme".to_string());
+/// let diagnostic = &error.to_diagnostics()[0];
+///
+/// assert_eq!(diagnostic.severity, Severity::Error);
+/// assert_eq!(diagnostic.code, "RNA0002");
+/// assert_eq!(diagnostic.namespace.as_deref(), Some("minecraft:itme"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	/// Stable error code (i.e. `RNA0001`)
+	pub code: String,
+	pub message: String,
+	/// File that cause this diagnostic (if known)
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub file: Option<PathBuf>,
+	/// Where in `file` (if known)
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub span: Option<Span>,
+	/// Namespace, pattern or type name that this diagnostic is about
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub namespace: Option<String>,
+	/// Pool (key of `pools`) that this diagnostic is in, when `namespace` is about something inside it
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub pool: Option<String>
+}
+
+impl Diagnostic {
+	pub fn new(severity: Severity, code: impl Into<String>, message: impl Into<String>) -> Diagnostic {
+		Diagnostic {
+			severity,
+			code: code.into(),
+			message: message.into(),
+			file: None,
+			span: None,
+			namespace: None,
+			pool: None
+		}
+	}
+
+	/// Create error diagnostic, `message` is the `Display` of `value` without colors
+	fn error(code: &str, value: &impl fmt::Display) -> Diagnostic {
+		Diagnostic::new(Severity::Error, code, plain(value))
+	}
+
+	/// Create warning diagnostic, `message` is the `Display` of `value` without colors
+	fn warning(code: &str, value: &impl fmt::Display) -> Diagnostic {
+		Diagnostic::new(Severity::Warning, code, plain(value))
+	}
+
+	fn file(mut self, file: Option<PathBuf>) -> Diagnostic {
+		self.file = file;
+		self
+	}
+
+	fn namespace(mut self, namespace: impl Into<String>) -> Diagnostic {
+		self.namespace = Some(namespace.into());
+		self
+	}
+
+	/// Name the pool of an error inside it, `message` is replaced with the `Display` of `value` which already name the pool
+	fn pool(self, pool: &str, value: &impl fmt::Display) -> Diagnostic {
+		Diagnostic { message: plain(value), pool: Some(pool.to_string()), ..self }
+	}
+
+	/// Serialize this diagnostic into a single line of JSON
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).expect("diagnostic is always valid JSON")
+	}
+}
+
+/// Create diagnostic from JSON error, position is moved from `message` into `span`
+fn json_error(error: &serde_json::Error) -> Diagnostic {
+	let mut diagnostic = Diagnostic::error(code::INVALID_JSON, error);
+	if error.line() > 0 {
+		let suffix = format!(" at line {} column {}", error.line(), error.column());
+		if diagnostic.message.ends_with(&suffix) {
+			let length = diagnostic.message.len() - suffix.len();
+			diagnostic.message.truncate(length);
+		}
+		diagnostic.span = Some(Span::new(error.line(), error.column()));
+	}
+
+	diagnostic
+}
+
+/// Fill `file` of every diagnostic that doesn't know its file yet
+fn in_file(diagnostics: Vec<Diagnostic>, file: &Path) -> Vec<Diagnostic> {
+	diagnostics
+		.into_iter()
+		.map(|mut diagnostic| {
+			diagnostic.file.get_or_insert_with(|| file.to_path_buf());
+			diagnostic
+		})
+		.collect()
+}
+
+/// Convert errors and warnings into `Diagnostic`.
+///
+/// Error that contain several errors (i.e. `ScriptFormatError::Multiple`) give one diagnostic per error.
+pub trait ToDiagnostics {
+	fn to_diagnostics(&self) -> Vec<Diagnostic>;
+}
+
+impl ToDiagnostics for DecodeError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		let diagnostic = match self {
+			DecodeError::RegexError(_) => Diagnostic::error(code::REGEX, self),
+			DecodeError::InvalidNamespace(value) => Diagnostic::error(code::INVALID_NAMESPACE, self).namespace(value),
+			DecodeError::TooManyColons(value) => Diagnostic::error(code::TOO_MANY_COLONS, self).namespace(value)
+		};
+
+		vec![diagnostic]
+	}
+}
+
+impl ToDiagnostics for DropTypeError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			DropTypeError::DecodeError(error) => error.to_diagnostics(),
+			DropTypeError::NotAllow(kind) => vec![Diagnostic::error(code::UNSAFE_NOT_ALLOW, self).namespace(kind)],
+			DropTypeError::InvalidType(kind) => vec![Diagnostic::error(code::INVALID_DROP_TYPE, self).namespace(kind)],
+			DropTypeError::NestedPool => vec![Diagnostic::error(code::INVALID_DROP_TYPE, self).namespace(DropType::Pool.name())],
+			DropTypeError::Condition(error) => error.to_diagnostics(),
+			DropTypeError::Function(error) => error.to_diagnostics()
+		}
+	}
+}
+
+impl ToDiagnostics for ConditionError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		let diagnostic = Diagnostic::error(code::INVALID_CONDITION, self);
+		match self.condition() {
+			Some(condition) => vec![diagnostic.namespace(condition)],
+			None => vec![diagnostic]
+		}
+	}
+}
+
+impl ToDiagnostics for FunctionError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		if let FunctionError::Condition(error) = self {
+			return error.to_diagnostics();
+		}
+
+		let diagnostic = Diagnostic::error(code::INVALID_FUNCTION, self);
+		match self.function() {
+			Some(function) => vec![diagnostic.namespace(function)],
+			None => vec![diagnostic]
+		}
+	}
+}
+
+impl ToDiagnostics for ExtensionError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			ExtensionError::DecodeError(error) => error.to_diagnostics(),
+			ExtensionError::NotFound(name) => vec![Diagnostic::error(code::EXTENSION_NOT_FOUND, self).namespace(name)],
+			ExtensionError::Cycle(chain) => {
+				let diagnostic = Diagnostic::error(code::EXTEND_CYCLE, self);
+				vec![match chain.first() {
+					Some(name) => diagnostic.namespace(name),
+					None => diagnostic
+				}]
+			}
+		}
+	}
+}
+
+impl ToDiagnostics for ScriptFormatError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			ScriptFormatError::Extension(error) => error.to_diagnostics(),
+			ScriptFormatError::DropType(error) => error.to_diagnostics(),
+			ScriptFormatError::Decode(error) => error.to_diagnostics(),
+			ScriptFormatError::UnknownField { pool, .. } => {
+				let diagnostic = Diagnostic::error(code::UNKNOWN_FIELD, self);
+				vec![match pool {
+					Some(pool) => diagnostic.namespace(pool),
+					None => diagnostic
+				}]
+			},
+			ScriptFormatError::Condition { pool, error } => error
+				.to_diagnostics()
+				.into_iter()
+				.map(|diagnostic| diagnostic.pool(pool, self))
+				.collect(),
+			ScriptFormatError::Function { pool, error } => error
+				.to_diagnostics()
+				.into_iter()
+				.map(|diagnostic| diagnostic.pool(pool, self))
+				.collect(),
+			ScriptFormatError::At(span, error) => error
+				.to_diagnostics()
+				.into_iter()
+				.map(|mut diagnostic| {
+					diagnostic.span.get_or_insert(*span);
+					diagnostic
+				})
+				.collect(),
+			ScriptFormatError::Multiple(errors) => errors.iter().flat_map(ToDiagnostics::to_diagnostics).collect()
+		}
+	}
+}
+
+impl ToDiagnostics for ReadError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			ReadError::ScriptFormat(error) => error.to_diagnostics(),
+			ReadError::Io(error) => vec![Diagnostic::error(code::IO, error)],
+			ReadError::Serde(error) => vec![json_error(error)]
+		}
+	}
+}
+
+impl ToDiagnostics for MeguError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			MeguError::NotExist(path) => vec![Diagnostic::error(code::NOT_EXIST, self).file(Some(path.clone()))],
+			MeguError::NotAFile(path) => vec![Diagnostic::error(code::NOT_A_FILE, self).file(Some(path.clone()))],
+			MeguError::Read((path, error)) => in_file(error.to_diagnostics(), path),
+			MeguError::Merge((Some(path), error)) => in_file(error.to_diagnostics(), path),
+			MeguError::Merge((None, error)) => error.to_diagnostics(),
+			MeguError::Conflict(conflict) => vec![Diagnostic::error(code::POOL_CONFLICT, conflict)
+				.file(conflict.second.path.clone())
+				.namespace(conflict.key.to_string())]
+		}
+	}
+}
+
+impl ToDiagnostics for MetaError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		let diagnostic = match self {
+			MetaError::NotExist(path) => Diagnostic::error(code::NOT_EXIST, self).file(Some(path.clone())),
+			MetaError::NotAFile(path) => Diagnostic::error(code::NOT_A_FILE, self).file(Some(path.clone())),
+			MetaError::Serde((path, error)) => json_error(error).file(Some(path.clone())),
+			MetaError::Io((path, error)) => Diagnostic::error(code::IO, error).file(Some(path.clone())),
+			MetaError::NoCompilerOptions(path) => Diagnostic::error(code::NO_COMPILER_OPTIONS, self).file(Some(path.clone())),
+			MetaError::InvalidOption((path, error)) => Diagnostic::error(code::INVALID_OPTION, error).file(Some(path.clone())).namespace(&error.name)
+		};
+
+		vec![diagnostic]
+	}
+}
+
+impl ToDiagnostics for DatapackError {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		match self {
+			DatapackError::Meta(error) => error.to_diagnostics(),
+			DatapackError::Io((path, error)) => vec![Diagnostic::error(code::IO, error).file(Some(path.clone()))],
+			DatapackError::Megu(error) => error.to_diagnostics()
+		}
+	}
+}
+
+impl ToDiagnostics for Warning {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		let diagnostic = match self {
+			Warning::Conflict(conflict) => Diagnostic::warning(code::POOL_CONFLICT, conflict)
+				.file(conflict.second.path.clone())
+				.namespace(conflict.key.to_string()),
+			Warning::UnmatchedRemove { pattern, script, extend } => Diagnostic::warning(code::UNMATCHED_REMOVE, self)
+				.file(extend.as_ref().map(|extension| extension.location().to_path_buf()).or_else(|| script.path.clone()))
+				.namespace(pattern.as_str()),
+			Warning::UnknownOption { name, path } => Diagnostic::warning(code::UNKNOWN_OPTION, self)
+				.file(Some(path.clone()))
+				.namespace(name)
+		};
+
+		vec![diagnostic]
+	}
+}
+
+impl ToDiagnostics for Diagnostics {
+	fn to_diagnostics(&self) -> Vec<Diagnostic> {
+		self.warnings.iter().flat_map(ToDiagnostics::to_diagnostics).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::MeguScript;
+	use crate::megu::script::ParseOptions;
+
+	#[test]
+	fn error_into_json_diagnostics() {
+		let content = b"{\n  \"pools\": {\n    \"boomber:ruby\": { \"type\": \"minecraft:itme\" },\n    \"Boomber:x\": { \"type\": \"item\" }\n  }\n}";
+		let error = MeguScript::from_slice(content, "resource", ParseOptions::collect_errors()).unwrap_err();
+		let error = MeguError::Read((PathBuf::from("stone.ult"), error));
+		let lines: Vec<String> = error.to_diagnostics().iter().map(Diagnostic::to_json).collect();
+
+		assert_eq!(lines, [
+			r#"{"severity":"error","code":"RNA0002","message":"'minecraft:itme' is not a valid type name.","file":"stone.ult","span":{"line":3,"column":31},"namespace":"minecraft:itme"}"#,
+			r#"{"severity":"error","code":"RNA0003","message":"'Boomber:x' contain invalid character (/^[a-z:._\\-/\\d]+$/)","file":"stone.ult","span":{"line":4,"column":5},"namespace":"Boomber:x"}"#
+		]);
+	}
+
+	#[test]
+	fn keep_inner_namespace_in_pool() {
+		let content = b"{\n  \"pools\": {\n    \"boomber:ruby\": { \"type\": \"item\", \"functions\": [{ \"function\": \"minecraft:set_count\" }] }\n  }\n}";
+		let error = MeguScript::from_slice(content, "resource", ParseOptions::default()).unwrap_err();
+		let diagnostic = &error.to_diagnostics()[0];
+
+		assert_eq!(diagnostic.code, code::INVALID_FUNCTION);
+		assert_eq!(diagnostic.message, "In 'boomber:ruby' pool: 'minecraft:set_count' function require 'count' field.");
+		assert_eq!(diagnostic.namespace.as_deref(), Some("minecraft:set_count"));
+		assert_eq!(diagnostic.pool.as_deref(), Some("boomber:ruby"));
+	}
+
+	#[test]
+	fn strip_position_from_json_error() {
+		let error = MeguScript::from_slice(b"{\n  \"pools\": [", "resource", ParseOptions::default()).unwrap_err();
+		let diagnostic = &error.to_diagnostics()[0];
+
+		assert_eq!(diagnostic.code, code::INVALID_JSON);
+		assert!(!diagnostic.message.contains(" at line "));
+		assert_eq!(diagnostic.span.map(|span| span.line), Some(2));
+	}
+}
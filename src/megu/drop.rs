@@ -1,6 +1,7 @@
 /// Possible type within `type` field of MeguDrop.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DropType {
+	#[default]
 	Item,
 	Tag,
 	LootTable,
@@ -8,13 +9,9 @@ pub enum DropType {
 	Alternatives,
 	Sequence,
 	Dynamic,
-	Empty
-}
-
-impl Default for DropType {
-	fn default() -> DropType {
-		DropType::Item
-	}
+	Empty,
+	/// Whole loot pool (`rna:pool`) with its entries as `children`, only allowed directly inside `pools` field
+	Pool
 }
 
 impl DropType {
@@ -37,81 +34,291 @@ impl DropType {
 				"empty" => DropType::Empty,
 				_ => return Err(DropTypeError::InvalidType(value))
 			},
+			"rna" => match namespace.suffix.as_ref() {
+				"pool" => DropType::Pool,
+				_ => return Err(DropTypeError::InvalidType(value))
+			},
 			_ => return Err(DropTypeError::InvalidType(value))
 		};
 
 		Ok(kind)
 	}
+
+	/// Get the vanilla name of this DropType (i.e. `minecraft:item`)
+	pub fn name(self) -> &'static str {
+		match self {
+			DropType::Item => "minecraft:item",
+			DropType::Tag => "minecraft:tag",
+			DropType::LootTable => "minecraft:loot_table",
+			DropType::Group => "minecraft:group",
+			DropType::Alternatives => "minecraft:alternatives",
+			DropType::Sequence => "minecraft:sequence",
+			DropType::Dynamic => "minecraft:dynamic",
+			DropType::Empty => "minecraft:empty",
+			DropType::Pool => "rna:pool"
+		}
+	}
 }
 
 use serde_json::Value;
 /// A data structure representing each item in the `pools` field.
 #[derive(Debug, Clone, PartialEq)]
 pub struct MeguDrop {
+	pub(crate) r#unsafe: bool,
+	pub(crate) kind: DropType,
+	pub(crate) name: Option<String>,
+	pub(crate) children: Option<Vec<MeguDrop>>,
+	pub(crate) conditions: Vec<Condition>,
+	pub(crate) functions: Vec<LootFunction>,
+	pub(crate) rolls: Option<NumberProvider>,
+	pub(crate) bonus_rolls: Option<NumberProvider>,
+	pub(crate) weight: Option<u32>,
+	pub(crate) quality: Option<i32>,
+	pub(crate) written: Written
+}
+
+/// How the optional fields of a drop were written, so `DropFormat::from()` can write them back the same way.
+///
+/// Each field keep what was read along with the JSON it was read from, which is only written back when the drop still hold the same value.
+/// It never take part in comparison, two drops that only differ by how they were written are equal.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Written {
 	r#unsafe: bool,
-	kind: DropType,
-	name: Option<String>,
-	children: Option<Vec<MeguDrop>>,
-	conditions: Vec<Value>,
-	functions: Vec<Value>
+	conditions: Option<(Vec<Condition>, Vec<Value>)>,
+	functions: Option<(Vec<LootFunction>, Vec<Value>)>,
+	rolls: Option<(NumberProvider, Value)>,
+	bonus_rolls: Option<(NumberProvider, Value)>
+}
+
+impl PartialEq for Written {
+	fn eq(&self, _: &Written) -> bool {
+		true
+	}
 }
 
-use super::{Namespace, DecodeError};
+impl Written {
+	/// JSON that `read` was read from when `value` is still the same, otherwise write `value` with `write`
+	fn get<T: PartialEq, V: Clone>(written: &Option<(T, V)>, value: &T, write: impl FnOnce(&T) -> V) -> V {
+		match written {
+			Some((read, original)) if read == value => original.clone(),
+			_ => write(value)
+		}
+	}
+}
+
+use super::{Namespace, DecodeError, NumberProvider};
+use super::number;
+use super::condition::{Condition, ConditionError};
+use super::function::{LootFunction, FunctionError};
+use super::builder::DropBuilder;
+use super::span::{self, Location};
+use super::script::ParseOptions;
 impl MeguDrop {
-	pub(crate) fn new(kind: impl Into<DropType>, name: impl Into<Option<String>>, children: impl Into<Option<Vec<MeguDrop>>>, conditions: impl Into<Vec<Value>>, functions: impl Into<Vec<Value>>, r#unsafe: bool) -> MeguDrop {
+	pub(crate) fn new(kind: impl Into<DropType>, name: impl Into<Option<String>>, children: impl Into<Option<Vec<MeguDrop>>>, conditions: impl Into<Vec<Condition>>, functions: impl Into<Vec<LootFunction>>, r#unsafe: bool) -> MeguDrop {
 		let kind = kind.into();
 		let name = name.into();
 		let children = children.into();
 		let conditions = conditions.into();
 		let functions = functions.into();
 		
-		MeguDrop { kind, name, children, conditions, functions, r#unsafe }
+		MeguDrop { kind, name, children, conditions, functions, r#unsafe, rolls: None, bonus_rolls: None, weight: None, quality: None, written: Written::default() }
 	}
 
 	/// Check for "unsafe" type which is usually a type that can create nested structure inside loot table.
-	fn is_unsafe(kind: DropType) -> bool {
-		match kind {
-			DropType::Alternatives |
-			DropType::Group |
-			DropType::Sequence
-			=> true,
-			_ => false
+	pub(crate) fn is_unsafe(kind: DropType) -> bool {
+		matches!(kind, DropType::Alternatives | DropType::Group | DropType::Sequence | DropType::Pool)
+	}
+
+	/// `rna:pool` can't be a child of another drop since loot entry can't contain a pool
+	pub(crate) fn check_children(children: &[MeguDrop]) -> Result<(), DropTypeError> {
+		if children.iter().any(|child| child.kind == DropType::Pool) {
+			return Err(DropTypeError::NestedPool);
+		}
+
+		Ok(())
+	}
+
+	/// Unsafe type must come with `"unsafe": true` keyword, and safe type must not.
+	/// `original` is the type name as written by the user.
+	pub(crate) fn check_unsafe(kind: DropType, r#unsafe: bool, original: &str) -> Result<(), DropTypeError> {
+		if r#unsafe != MeguDrop::is_unsafe(kind) {
+			return Err(DropTypeError::NotAllow(original.to_string()));
 		}
+
+		Ok(())
+	}
+
+	/// Start building MeguDrop of the given type, see `DropBuilder`
+	pub fn builder(kind: DropType) -> DropBuilder {
+		DropBuilder::new(kind)
+	}
+
+	pub fn kind(&self) -> DropType {
+		self.kind
+	}
+
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	pub fn children(&self) -> Option<&[MeguDrop]> {
+		self.children.as_deref()
+	}
+
+	pub fn conditions(&self) -> &[Condition] {
+		&self.conditions
+	}
+
+	pub fn functions(&self) -> &[LootFunction] {
+		&self.functions
+	}
+
+	/// Whether this drop was marked with `"unsafe": true` keyword
+	pub fn r#unsafe(&self) -> bool {
+		self.r#unsafe
+	}
+
+	pub fn rolls(&self) -> Option<&NumberProvider> {
+		self.rolls.as_ref()
+	}
+
+	pub fn bonus_rolls(&self) -> Option<&NumberProvider> {
+		self.bonus_rolls.as_ref()
+	}
+
+	pub fn weight(&self) -> Option<u32> {
+		self.weight
+	}
+
+	pub fn quality(&self) -> Option<i32> {
+		self.quality
 	}
 
 	/// Create MeguDrop from a drop template for `serde_json` to interpret
 	/// 
 	/// This method can fail if DropType is not valid.
 	pub fn from_drop_format(format: DropFormat) -> Result<MeguDrop, DropTypeError> {
-		let kind = DropType::get_drop_type(&format.r#type)?;
-		let name = format.name;
-		let children = MeguDrop::get_children(format.children)?;
-		let conditions = format.conditions.unwrap_or_default();
-		let functions = format.functions.unwrap_or_default();
+		MeguDrop::from_drop_format_at(format, "", ParseOptions::default()).map_err(|mut errors| errors.remove(0).1)
+	}
 
-		let r#unsafe = format.r#unsafe.unwrap_or_default();
+	/// Same as `from_drop_format()` but collect every error in this drop and its children,
+	/// each error also point at `type` field that cause it. `pointer` is JSON pointer of this drop.
+	/// Conditions and functions are read with `options`.
+	/// 
+	/// The returned errors are never empty.
+	pub(crate) fn from_drop_format_at(format: DropFormat, pointer: &str, options: ParseOptions) -> Result<MeguDrop, Vec<(Location, DropTypeError)>> {
+		let mut errors = Vec::default();
+		let location = Location::Value(format!("{}/type", pointer));
 
-		if r#unsafe != MeguDrop::is_unsafe(kind) {
-			return Err(DropTypeError::NotAllow(format.r#type));
+		let kind = DropType::get_drop_type(&format.r#type).map_err(|error| errors.push((location.clone(), error))).ok();
+		let r#unsafe = format.r#unsafe.unwrap_or_default();
+		if let Some(kind) = kind {
+			if let Err(error) = MeguDrop::check_unsafe(kind, r#unsafe, &format.r#type) {
+				errors.push((location, error));
+			}
 		}
 
-		let result = MeguDrop::new(kind, name, children, conditions, functions, r#unsafe);
+		let name = format.name;
+		let children = MeguDrop::get_children(format.children, pointer, options).unwrap_or_else(|mut result| {
+			errors.append(&mut result);
+			None
+		});
+		let conditions = MeguDrop::get_conditions(format.conditions.as_deref().unwrap_or_default(), pointer, options).unwrap_or_else(|mut result| {
+			errors.append(&mut result);
+			Vec::default()
+		});
+		let functions = MeguDrop::get_functions(format.functions.as_deref().unwrap_or_default(), pointer, options).unwrap_or_else(|mut result| {
+			errors.append(&mut result);
+			Vec::default()
+		});
+		// Already checked when `format` was deserialized
+		let number = |value: Value| (NumberProvider::from_value(&value).unwrap_or_else(|_| NumberProvider::Other(value.clone())), value);
+		let rolls = format.rolls.map(number);
+		let bonus_rolls = format.bonus_rolls.map(number);
+
+		let kind = match kind {
+			Some(kind) if errors.is_empty() => kind,
+			_ => return Err(errors)
+		};
+
+		let mut result = MeguDrop::new(kind, name, children, conditions, functions, r#unsafe);
+		result.rolls = rolls.as_ref().map(|(rolls, _)| rolls.clone());
+		result.bonus_rolls = bonus_rolls.as_ref().map(|(bonus_rolls, _)| bonus_rolls.clone());
+		result.weight = format.weight;
+		result.quality = format.quality;
+		result.written = Written {
+			r#unsafe: format.r#unsafe.is_some(),
+			conditions: format.conditions.map(|values| (result.conditions.clone(), values)),
+			functions: format.functions.map(|values| (result.functions.clone(), values)),
+			rolls,
+			bonus_rolls
+		};
 		
 		Ok(result)
 	}
 
-	/// Safely convert `DropFormat` to `MeguDrop`
-	fn get_children(format: Option<Vec<DropFormat>>) -> Result<Option<Vec<MeguDrop>>, DropTypeError> {
-		match format {
-			None => Ok(None),
-			Some(childs) => {
-				let result: Result<Vec<_>, _> = childs.into_iter().map(MeguDrop::from_drop_format).collect();
+	/// Validate every condition, each error point at the condition that cause it
+	fn get_conditions(format: &[Value], pointer: &str, options: ParseOptions) -> Result<Vec<Condition>, Vec<(Location, DropTypeError)>> {
+		let mut result = Vec::with_capacity(format.len());
+		let mut errors = Vec::default();
+		for (index, condition) in format.iter().enumerate() {
+			match Condition::from_value_with(condition, options) {
+				Ok(condition) => result.push(condition),
+				Err(error) => errors.push((Location::Value(format!("{}/conditions/{}", pointer, index)), error.into()))
+			}
+		}
+
+		if errors.is_empty() {
+			Ok(result)
+		}
+		else {
+			Err(errors)
+		}
+	}
+
+	/// Validate every function, each error point at the function that cause it
+	fn get_functions(format: &[Value], pointer: &str, options: ParseOptions) -> Result<Vec<LootFunction>, Vec<(Location, DropTypeError)>> {
+		let mut result = Vec::with_capacity(format.len());
+		let mut errors = Vec::default();
+		for (index, function) in format.iter().enumerate() {
+			match LootFunction::from_value_with(function, options) {
+				Ok(function) => result.push(function),
+				Err(error) => errors.push((Location::Value(format!("{}/functions/{}", pointer, index)), error.into()))
+			}
+		}
+
+		if errors.is_empty() {
+			Ok(result)
+		}
+		else {
+			Err(errors)
+		}
+	}
+
+	/// Safely convert `DropFormat` to `MeguDrop`, collecting errors from every child
+	fn get_children(format: Option<Vec<DropFormat>>, pointer: &str, options: ParseOptions) -> Result<Option<Vec<MeguDrop>>, Vec<(Location, DropTypeError)>> {
+		let childs = match format {
+			Some(childs) => childs,
+			None => return Ok(None)
+		};
 
-				// This is probably the stupidiest syntax I have ever seen.
-				// TODO: Fix this
-				Ok(Some(result?))
+		let mut result = Vec::with_capacity(childs.len());
+		let mut errors = Vec::default();
+		for (index, child) in childs.into_iter().enumerate() {
+			let pointer = format!("{}/children/{}", pointer, index);
+			match MeguDrop::from_drop_format_at(child, &pointer, options) {
+				Ok(child) if child.kind == DropType::Pool => errors.push((Location::Value(format!("{}/type", pointer)), DropTypeError::NestedPool)),
+				Ok(child) => result.push(child),
+				Err(mut error) => errors.append(&mut error)
 			}
 		}
+
+		if errors.is_empty() {
+			Ok(Some(result))
+		}
+		else {
+			Err(errors)
+		}
 	}
 }
 
@@ -129,22 +336,41 @@ pub enum DropTypeError {
 	/// Emit when you're trying to use unsafe type in a Drop without `"unsafe": true` keyword
 	NotAllow(String),
 	/// Emit when `type` field is not a valid type
-	InvalidType(String)
+	InvalidType(String),
+	/// Emit when `rna:pool` is used as a child of another drop
+	NestedPool,
+	/// Emit when there's something wrong in the `conditions` field
+	Condition(ConditionError),
+	/// Emit when there's something wrong in the `functions` field
+	Function(FunctionError)
 }
 
-use colored::*;
+use super::render::Paint;
 use std::fmt;
 impl From<DecodeError> for DropTypeError {
 	fn from(error: DecodeError) -> DropTypeError {
 		DropTypeError::DecodeError(error)
 	}
 }
+impl From<ConditionError> for DropTypeError {
+	fn from(error: ConditionError) -> DropTypeError {
+		DropTypeError::Condition(error)
+	}
+}
+impl From<FunctionError> for DropTypeError {
+	fn from(error: FunctionError) -> DropTypeError {
+		DropTypeError::Function(error)
+	}
+}
 impl fmt::Display for DropTypeError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
 			DropTypeError::DecodeError(error) => write!(f, "{}", error),
 			DropTypeError::InvalidType(original) => write!(f, "'{}' is not a valid type name.", original.cyan()),
+			DropTypeError::NestedPool => write!(f, "'{}' can only be used directly inside {} field.", DropType::Pool.name().cyan(), "pools".black().on_white()),
 			DropTypeError::NotAllow(kind) => write!(f, "'{}' is {} without {} keyword.", kind.cyan(), "not allow".red(), "unsafe".white().on_red()),
+			DropTypeError::Condition(error) => write!(f, "{}", error),
+			DropTypeError::Function(error) => write!(f, "{}", error)
 		}
 	}
 }
@@ -153,14 +379,90 @@ use serde::{Serialize, Deserialize};
 /// A template structure for `serde_json` to use.
 /// 
 /// `name` and `children` should never be `Some()` at the same time.
+/// 
+/// `rolls` and `bonus_rolls` belong to the pool this drop create while `weight` and `quality` belong to the entry itself,
+/// they're checked to be valid `NumberProvider` when deserialized but kept as they were written.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct DropFormat {
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub r#unsafe: Option<bool>,
 	pub r#type: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub children: Option<Vec<DropFormat>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub functions: Option<Vec<Value>>,
-	pub conditions: Option<Vec<Value>>
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub conditions: Option<Vec<Value>>,
+	#[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "number::deserialize_value")]
+	pub rolls: Option<Value>,
+	#[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "number::deserialize_value")]
+	pub bonus_rolls: Option<Value>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub weight: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub quality: Option<i32>
+}
+
+/// Convert MeguDrop back into drop template
+///
+/// Fields that didn't change since the drop was read are written exactly as they were,
+/// and optional fields that were missing stay missing as long as they're empty (or `false` for `unsafe`).
+impl From<&MeguDrop> for DropFormat {
+	fn from(drop: &MeguDrop) -> DropFormat {
+		let written = &drop.written;
+		let children = drop.children
+			.as_ref()
+			.map(|children| children.iter().map(DropFormat::from).collect());
+		let r#unsafe = if written.r#unsafe || drop.r#unsafe { Some(drop.r#unsafe) } else { None };
+		let conditions = match written.conditions {
+			None if drop.conditions.is_empty() => None,
+			_ => Some(Written::get(&written.conditions, &drop.conditions, |conditions| conditions.iter().map(Condition::to_value).collect()))
+		};
+		let functions = match written.functions {
+			None if drop.functions.is_empty() => None,
+			_ => Some(Written::get(&written.functions, &drop.functions, |functions| functions.iter().map(LootFunction::to_value).collect()))
+		};
+		let number = |written: &Option<(NumberProvider, Value)>, value: &Option<NumberProvider>| {
+			value.as_ref().map(|value| Written::get(written, value, NumberProvider::to_value))
+		};
+
+		DropFormat {
+			r#unsafe,
+			r#type: drop.kind.name().to_string(),
+			name: drop.name.clone(),
+			children,
+			functions,
+			conditions,
+			rolls: number(&written.rolls, &drop.rolls),
+			bonus_rolls: number(&written.bonus_rolls, &drop.bonus_rolls),
+			weight: drop.weight,
+			quality: drop.quality
+		}
+	}
+}
+
+/// Every field that `DropFormat` understand
+const FIELDS: &[&str] = &["unsafe", "type", "name", "children", "functions", "conditions", "rolls", "bonus_rolls", "weight", "quality"];
+
+/// Find every field inside `value` (and its children) that `DropFormat` doesn't understand.
+///
+/// Each field is pushed into `result` along with its JSON pointer, `pointer` is JSON pointer of `value`.
+pub(crate) fn unknown_fields(value: &Value, pointer: &str, result: &mut Vec<(String, String)>) {
+	let map = match value.as_object() {
+		Some(map) => map,
+		None => return
+	};
+
+	for key in map.keys().filter(|key| !FIELDS.contains(&key.as_str())) {
+		result.push((key.clone(), format!("{}{}", pointer, span::pointer(&[key]))));
+	}
+
+	let children = map.get("children").and_then(Value::as_array);
+	for (index, child) in children.into_iter().flatten().enumerate() {
+		unknown_fields(child, &format!("{}/children/{}", pointer, index), result);
+	}
 }
 
 #[cfg(test)]
@@ -177,8 +479,52 @@ mod tests {
 		assert_eq!(DropType::get_drop_type("item"), Ok(DropType::Item));
 	}
 
+	#[test]
+	fn keep_probabilities_from_drop_format() {
+		let value = r#"
+		{
+			"type": "minecraft:item",
+			"name": "minecraft:diamond",
+			"rolls": { "min": 1, "max": 3, "type": "minecraft:uniform" },
+			"bonus_rolls": 1,
+			"weight": 5,
+			"quality": -2
+		}
+		"#;
+		let format: DropFormat = serde_json::from_str(value).unwrap();
+		let drop = MeguDrop::from_drop_format(format).unwrap();
+
+		assert_eq!(drop.rolls, Some(NumberProvider::Uniform { min: 1.0, max: 3.0 }));
+		assert_eq!(drop.bonus_rolls, Some(NumberProvider::Constant(1.0)));
+		assert_eq!(drop.weight, Some(5));
+		assert_eq!(drop.quality, Some(-2));
+	}
+
 	#[test]
 	fn is_drop_type_unsafe() {
 		assert!(MeguDrop::is_unsafe(DropType::Alternatives));
+		assert!(MeguDrop::is_unsafe(DropType::Pool));
+	}
+
+	#[test]
+	fn get_pool_drop_type() {
+		assert_eq!(DropType::get_drop_type("rna:pool"), Ok(DropType::Pool));
+		assert!(DropType::get_drop_type("pool").is_err());
+	}
+
+	#[test]
+	fn reject_nested_pool() {
+		let value = r#"
+		{
+			"type": "minecraft:alternatives",
+			"unsafe": true,
+			"children": [
+				{ "type": "rna:pool", "unsafe": true, "children": [] }
+			]
+		}
+		"#;
+		let format: DropFormat = serde_json::from_str(value).unwrap();
+
+		assert_eq!(MeguDrop::from_drop_format(format), Err(DropTypeError::NestedPool));
 	}
 }
\ No newline at end of file
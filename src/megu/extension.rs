@@ -1,5 +1,7 @@
-use super::{Namespace, DecodeError, MeguScript, ReadError};
-use std::path::PathBuf;
+use super::{DecodeError, MeguScript, ReadError, ParseOptions, Resolver};
+use super::vanilla;
+use std::path::{Path, PathBuf};
+use std::io;
 
 /// Extension Script of MeguScript
 /// 
@@ -7,8 +9,10 @@ use std::path::PathBuf;
 /// To refer to `creeper` loot table use `minecraft:entities/creeper`
 /// 
 /// # Examples
-/// ```should_panic
+/// With `vanilla` feature, vanilla loot tables are always available.
+/// ```
 /// # use rna::Extension;
+/// # #[cfg(feature = "vanilla")]
 /// let creeper_extend = Extension::get_extension("minecraft:entities/creeper", "resource").unwrap();
 /// ```
 /// If the input string is not a vanilla's path it will panic
@@ -18,40 +22,87 @@ use std::path::PathBuf;
 /// ```
 #[derive(Clone, PartialEq, Eq, Debug, PartialOrd)]
 pub struct Extension {
-	location: PathBuf
+	name: String,
+	location: PathBuf,
+	origin: Origin
+}
+
+/// Where an `Extension` was found
+#[derive(Clone, PartialEq, Eq, Debug, PartialOrd)]
+pub enum Origin {
+	/// Found under this search root of `Resolver`
+	Root(PathBuf),
+	/// Created directly from a file with `Extension::new()`
+	File,
+	/// Embedded vanilla loot table database
+	Vanilla
 }
 
 impl Extension {
 	pub fn new(location: impl Into<PathBuf>) -> Extension {
 		let location = location.into();
-		Extension { location }
+		let name = location.display().to_string();
+		Extension { name, location, origin: Origin::File }
+	}
+
+	pub(crate) fn with_origin(name: impl Into<String>, location: impl Into<PathBuf>, origin: Origin) -> Extension {
+		let name = name.into();
+		let location = location.into();
+		Extension { name, location, origin }
 	}
 
 	/// Get extension from given Namespace
 	/// 
 	/// # Setting up
-	/// You need to include the source files for the loot table database yourself.
+	/// With `vanilla` feature (enabled by default), vanilla loot tables are embedded inside this crate.
 	/// 
-	/// Which you need to place the file in this order: `{base_path}/{prefix}/{suffix}.ult` where `prefix` and `suffix` refer to Namespace
-	pub fn get_extension(value: impl Into<String>, base_path: impl Into<PathBuf>) -> Result<Extension, ExtensionError> {
-		let value = value.into();
-		let namespace = Namespace::decode(&value)?;
-	
-		let path: PathBuf = base_path.into();
-		let path = path
-			.join(namespace.prefix)
-			.join(format!("{}.ult", namespace.suffix));
-	
-		if !path.exists() {
-			return Err(ExtensionError::NotFound(value));
-		}
-	
-		Ok(Extension::new(path))
+	/// Otherwise you need to include the source files for the loot table database yourself.
+	/// Which you need to place the file in this order: `{root}/{prefix}/{suffix}.ult` where `prefix` and `suffix` refer to Namespace (`.megu` and `.json.merge` also work).
+	/// 
+	/// `resolver` can be a single `base_path` or a `Resolver` with multiple roots, see `Resolver::resolve()`.
+	pub fn get_extension(value: impl Into<String>, resolver: impl Into<Resolver>) -> Result<Extension, ExtensionError> {
+		resolver.into().resolve(value)
+	}
+
+	/// Name that was used to refer to this extension (i.e. `minecraft:entities/creeper`)
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Path to the file of this extension
+	pub fn location(&self) -> &Path {
+		&self.location
+	}
+
+	/// Where this extension was found
+	pub fn origin(&self) -> &Origin {
+		&self.origin
+	}
+
+	/// Check if this extension come from embedded vanilla loot table database
+	pub fn is_vanilla(&self) -> bool {
+		self.origin == Origin::Vanilla
 	}
 
 	/// Create MeguScript from this Extension
-	pub fn compile(&self, base_path: impl Into<PathBuf>) -> Result<MeguScript, ReadError> {
-		MeguScript::from_path(&self.location, base_path)
+	pub fn compile(&self, resolver: impl Into<Resolver>) -> Result<MeguScript, ReadError> {
+		self.compile_with(resolver, ParseOptions::default())
+	}
+
+	/// Same as `compile()` but the script is read with the given `ParseOptions`
+	pub fn compile_with(&self, resolver: impl Into<Resolver>, options: ParseOptions) -> Result<MeguScript, ReadError> {
+		match self.origin {
+			Origin::Root(_) | Origin::File => MeguScript::from_path_with(&self.location, resolver, options),
+			Origin::Vanilla => {
+				let location = self.location.to_string_lossy();
+				let content = match vanilla::get(&location) {
+					Some(content) => content,
+					None => return Err(ReadError::Io(io::Error::new(io::ErrorKind::NotFound, format!("'{}' is not embedded", location))))
+				};
+
+				MeguScript::from_slice(content, resolver, options)
+			}
+		}
 	}
 }
 
@@ -61,11 +112,14 @@ pub enum ExtensionError {
 	/// Emit when Namespace is invalid
 	DecodeError(DecodeError),
 	/// Emit when cannot find extension with that name
-	NotFound(String)
+	NotFound(String),
+	/// Emit when `extend` chain loop back to an extension that's already in the chain.
+	/// The full chain is attached to this error.
+	Cycle(Vec<String>)
 }
 
 use std::fmt;
-use colored::*;
+use super::render::Paint;
 impl From<DecodeError> for ExtensionError {
 	fn from(error: DecodeError) -> ExtensionError {
 		ExtensionError::DecodeError(error)
@@ -75,7 +129,23 @@ impl fmt::Display for ExtensionError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
 			ExtensionError::DecodeError(error) => write!(f, "{}", error),
-			ExtensionError::NotFound(value) => write!(f, "Does not recognized '{}' in {} field.", value.cyan(), "extend".black().on_white())
+			ExtensionError::NotFound(value) => write!(f, "Does not recognized '{}' in {} field.", value.cyan(), "extend".black().on_white()),
+			ExtensionError::Cycle(chain) => write!(f, "Found cycle in {} field: {}", "extend".black().on_white(), chain.join(" -> ").cyan())
 		}
 	}
 }
+
+#[cfg(all(test, feature = "vanilla"))]
+mod tests {
+	use super::*;
+	use crate::megu::Namespace;
+
+	#[test]
+	fn resolve_embedded_extension() {
+		let extension = Extension::get_extension("minecraft:entities/creeper", "this/path/does/not/exists").unwrap();
+		assert!(extension.is_vanilla());
+
+		let script = extension.compile("this/path/does/not/exists").unwrap();
+		assert!(script.pools.contains_key(&Namespace::new("minecraft", "minecraft/entities/creeper/gunpowder")));
+	}
+}
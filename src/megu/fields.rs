@@ -0,0 +1,70 @@
+use serde_json::{Value, Map};
+use std::convert::TryFrom;
+
+/// Problem with a single field, turned into `ConditionError` or `FunctionError` by `Fields`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldError<'a> {
+	Unknown(&'a str),
+	Missing(&'a str),
+	Invalid { field: &'a str, expected: &'a str }
+}
+
+/// Fields of a known condition or function named `name`
+pub(crate) struct Fields<'a, E> {
+	name: &'a str,
+	map: &'a Map<String, Value>,
+	error: fn(&str, FieldError) -> E
+}
+
+impl<'a, E> Fields<'a, E> {
+	/// Every field inside `map` must be in `known` unless `strict` is `false`, `tag` is the field that hold the name (i.e. `condition`)
+	pub(crate) fn new(name: &'a str, map: &'a Map<String, Value>, tag: &str, known: &[&str], strict: bool, error: fn(&str, FieldError) -> E) -> Result<Fields<'a, E>, E> {
+		if let Some(key) = map.keys().find(|key| strict && *key != tag && !known.contains(&key.as_str())) {
+			return Err(error(name, FieldError::Unknown(key)));
+		}
+
+		Ok(Fields { name, map, error })
+	}
+
+	/// Read `field` with `read`, which return `None` when the value doesn't look like `expected`
+	pub(crate) fn optional<T>(&self, field: &str, expected: &str, read: impl FnOnce(&'a Value) -> Option<T>) -> Result<Option<T>, E> {
+		match self.map.get(field) {
+			Some(value) => read(value).map(Some).ok_or_else(|| (self.error)(self.name, FieldError::Invalid { field, expected })),
+			None => Ok(None)
+		}
+	}
+
+	/// Same as `optional()` but the field must exists
+	pub(crate) fn required<T>(&self, field: &str, expected: &str, read: impl FnOnce(&'a Value) -> Option<T>) -> Result<T, E> {
+		self.optional(field, expected, read)?.ok_or_else(|| (self.error)(self.name, FieldError::Missing(field)))
+	}
+}
+
+/// Call `insert` only when `value` is `Some()`
+pub(crate) fn optional<T: Into<Value>>(insert: &mut impl FnMut(&str, Value), key: &str, value: Option<T>) {
+	if let Some(value) = value {
+		insert(key, value.into());
+	}
+}
+
+pub(crate) fn object(value: &Value) -> Option<&Value> {
+	value.as_object().map(|_| value)
+}
+
+pub(crate) fn integer(value: &Value) -> Option<i32> {
+	value.as_i64().and_then(|value| i32::try_from(value).ok())
+}
+
+/// Number or `{"min", "max"}` range, either bound can be missing
+pub(crate) fn range(value: &Value) -> Option<&Value> {
+	match value {
+		Value::Number(_) => Some(value),
+		Value::Object(map) if map.keys().all(|key| key == "min" || key == "max") && map.values().all(Value::is_number) => Some(value),
+		_ => None
+	}
+}
+
+/// Array of strings
+pub(crate) fn strings(value: &Value) -> Option<Vec<String>> {
+	value.as_array()?.iter().map(|value| value.as_str().map(str::to_string)).collect()
+}
@@ -0,0 +1,511 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::{Value, Map};
+use super::number::{NumberProvider, number};
+use super::condition::{Condition, ConditionError};
+use super::fields::{Fields, FieldError, optional, integer, range, strings};
+use super::script::ParseOptions;
+
+/// Loot function used in `functions` field, along with its own `conditions`.
+///
+/// # Examples
+/// ```
+/// # use rna::function::{LootFunction, Function};
+/// # use rna::number::NumberProvider;
+/// let value = r#"{"function":"minecraft:set_count","count":{"min":1,"max":3,"type":"minecraft:uniform"}}"#;
+/// let function: LootFunction = serde_json::from_str(value).unwrap();
+///
+/// assert_eq!(function.function, Function::SetCount { count: NumberProvider::Uniform { min: 1.0, max: 3.0 } });
+/// assert!(function.conditions.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LootFunction {
+	pub function: Function,
+	/// Conditions of this function, always empty for `Function::Other` which keep its conditions as they are
+	pub conditions: Vec<Condition>
+}
+
+/// Loot function without its conditions.
+///
+/// Known vanilla functions are validated when they're read, anything else is kept as `Other`.
+/// Known function with a field it doesn't use is also kept as `Other`, unless it's read with strict `ParseOptions`,
+/// but only when every field it does use is valid.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Function {
+	/// Set stack size to `count`
+	SetCount { count: NumberProvider },
+	/// Add `count` (number or `{"min", "max"}` without type) per looting level, capped at `limit`
+	LootingEnchant { count: NumberProvider, limit: Option<i32> },
+	/// Add bonus from `enchantment` level with `formula` (i.e. `minecraft:ore_drops`)
+	ApplyBonus { enchantment: String, formula: String, parameters: Option<Map<String, Value>> },
+	/// Set NBT of the item, `tag` is SNBT string
+	SetNbt { tag: String },
+	/// Remove some items when the drop is caused by explosion
+	ExplosionDecay,
+	/// Enchant with random enchantment from `enchantments` (or any enchantment when it's `None`)
+	EnchantRandomly { enchantments: Option<Vec<String>> },
+	/// Enchant as if using enchanting table with `levels`
+	EnchantWithLevels { levels: NumberProvider, treasure: Option<bool> },
+	/// Set durability of the item, `damage` is number or `{"min", "max"}` without type
+	SetDamage { damage: NumberProvider },
+	/// Smelt the item as if it was put in a furnace
+	FurnaceSmelt,
+	/// Clamp stack size, `limit` is number or `{"min", "max"}` range
+	LimitCount { limit: Value },
+	/// Copy custom name from `source` (i.e. `block_entity`)
+	CopyName { source: String },
+	/// Copy NBT from `source` with `ops`
+	CopyNbt { source: String, ops: Vec<Value> },
+	/// Copy block state `properties` of `block`
+	CopyState { block: String, properties: Vec<String> },
+	/// Set content of the container to `entries`
+	SetContents { entries: Vec<Value> },
+	/// Set effects of suspicious stew
+	SetStewEffect { effects: Vec<Value> },
+	/// Turn empty map into explorer map
+	ExplorationMap {
+		destination: Option<String>,
+		decoration: Option<String>,
+		zoom: Option<i32>,
+		search_radius: Option<i32>,
+		skip_existing_chunks: Option<bool>
+	},
+	/// Set display name, `name` is JSON text
+	SetName { name: Value, entity: Option<String> },
+	/// Set lore, every line is JSON text
+	SetLore { lore: Vec<Value>, entity: Option<String>, replace: Option<bool> },
+	/// Add attribute `modifiers`
+	SetAttributes { modifiers: Vec<Value> },
+	/// Set skull owner to `entity`
+	FillPlayerHead { entity: String },
+	/// Function that this library doesn't know, kept as it is
+	Other(Value)
+}
+
+impl Function {
+	/// Namespace of this function (i.e. `minecraft:set_count`), `None` for `Other` without `function` field
+	pub fn name(&self) -> Option<&str> {
+		let name = match self {
+			Function::SetCount { .. } => "minecraft:set_count",
+			Function::LootingEnchant { .. } => "minecraft:looting_enchant",
+			Function::ApplyBonus { .. } => "minecraft:apply_bonus",
+			Function::SetNbt { .. } => "minecraft:set_nbt",
+			Function::ExplosionDecay => "minecraft:explosion_decay",
+			Function::EnchantRandomly { .. } => "minecraft:enchant_randomly",
+			Function::EnchantWithLevels { .. } => "minecraft:enchant_with_levels",
+			Function::SetDamage { .. } => "minecraft:set_damage",
+			Function::FurnaceSmelt => "minecraft:furnace_smelt",
+			Function::LimitCount { .. } => "minecraft:limit_count",
+			Function::CopyName { .. } => "minecraft:copy_name",
+			Function::CopyNbt { .. } => "minecraft:copy_nbt",
+			Function::CopyState { .. } => "minecraft:copy_state",
+			Function::SetContents { .. } => "minecraft:set_contents",
+			Function::SetStewEffect { .. } => "minecraft:set_stew_effect",
+			Function::ExplorationMap { .. } => "minecraft:exploration_map",
+			Function::SetName { .. } => "minecraft:set_name",
+			Function::SetLore { .. } => "minecraft:set_lore",
+			Function::SetAttributes { .. } => "minecraft:set_attributes",
+			Function::FillPlayerHead { .. } => "minecraft:fill_player_head",
+			Function::Other(value) => return value.get("function").and_then(Value::as_str)
+		};
+
+		Some(name)
+	}
+
+	/// Write every field of this function into `map`, except `function` and `conditions`
+	fn write(&self, map: &mut Map<String, Value>) {
+		let mut insert = |key: &str, value: Value| {
+			map.insert(key.to_string(), value);
+		};
+		let string = |value: &String| Value::from(value.as_str());
+
+		match self {
+			Function::Other(_) | Function::ExplosionDecay | Function::FurnaceSmelt => (),
+			Function::SetCount { count } => insert("count", count.to_value()),
+			Function::LootingEnchant { count, limit } => {
+				insert("count", bounds(count));
+				optional(&mut insert, "limit", *limit);
+			},
+			Function::ApplyBonus { enchantment, formula, parameters } => {
+				insert("enchantment", string(enchantment));
+				insert("formula", string(formula));
+				optional(&mut insert, "parameters", parameters.clone());
+			},
+			Function::SetNbt { tag } => insert("tag", string(tag)),
+			Function::EnchantRandomly { enchantments } => optional(&mut insert, "enchantments", enchantments.clone()),
+			Function::EnchantWithLevels { levels, treasure } => {
+				insert("levels", levels.to_value());
+				optional(&mut insert, "treasure", *treasure);
+			},
+			Function::SetDamage { damage } => insert("damage", bounds(damage)),
+			Function::LimitCount { limit } => insert("limit", limit.clone()),
+			Function::CopyName { source } => insert("source", string(source)),
+			Function::CopyNbt { source, ops } => {
+				insert("source", string(source));
+				insert("ops", Value::from(ops.clone()));
+			},
+			Function::CopyState { block, properties } => {
+				insert("block", string(block));
+				insert("properties", Value::from(properties.clone()));
+			},
+			Function::SetContents { entries } => insert("entries", Value::from(entries.clone())),
+			Function::SetStewEffect { effects } => insert("effects", Value::from(effects.clone())),
+			Function::ExplorationMap { destination, decoration, zoom, search_radius, skip_existing_chunks } => {
+				optional(&mut insert, "destination", destination.clone());
+				optional(&mut insert, "decoration", decoration.clone());
+				optional(&mut insert, "zoom", *zoom);
+				optional(&mut insert, "search_radius", *search_radius);
+				optional(&mut insert, "skip_existing_chunks", *skip_existing_chunks);
+			},
+			Function::SetName { name, entity } => {
+				insert("name", name.clone());
+				optional(&mut insert, "entity", entity.clone());
+			},
+			Function::SetLore { lore, entity, replace } => {
+				insert("lore", Value::from(lore.clone()));
+				optional(&mut insert, "entity", entity.clone());
+				optional(&mut insert, "replace", *replace);
+			},
+			Function::SetAttributes { modifiers } => insert("modifiers", Value::from(modifiers.clone())),
+			Function::FillPlayerHead { entity } => insert("entity", string(entity))
+		}
+	}
+
+	/// Read function named `name` from `map`, `conditions` field is allowed but ignored and unknown fields are too when `strict` is `false`
+	fn read(name: &str, map: &Map<String, Value>, value: &Value, strict: bool) -> Result<Function, FunctionError> {
+		let known = |known: &[&str]| {
+			let known: Vec<&str> = known.iter().copied().chain(Some("conditions")).collect();
+			Fields::new(name, map, "function", &known, strict, FunctionError::field)
+		};
+		let string = |value: &str| value.to_string();
+
+		let function = match name.trim_start_matches("minecraft:") {
+			"set_count" => Function::SetCount { count: known(&["count"])?.required("count", "number provider", provider)? },
+			"looting_enchant" => {
+				let fields = known(&["count", "limit"])?;
+				Function::LootingEnchant {
+					count: fields.required("count", "number or {\"min\", \"max\"}", read_bounds)?,
+					limit: fields.optional("limit", "integer", integer)?
+				}
+			},
+			"apply_bonus" => {
+				let fields = known(&["enchantment", "formula", "parameters"])?;
+				Function::ApplyBonus {
+					enchantment: fields.required("enchantment", "string", Value::as_str)?.to_string(),
+					formula: fields.required("formula", "string", Value::as_str)?.to_string(),
+					parameters: fields.optional("parameters", "object", Value::as_object)?.cloned()
+				}
+			},
+			"set_nbt" => Function::SetNbt { tag: known(&["tag"])?.required("tag", "string", Value::as_str)?.to_string() },
+			"explosion_decay" => {
+				known(&[])?;
+				Function::ExplosionDecay
+			},
+			"enchant_randomly" => Function::EnchantRandomly { enchantments: known(&["enchantments"])?.optional("enchantments", "array of strings", strings)? },
+			"enchant_with_levels" => {
+				let fields = known(&["levels", "treasure"])?;
+				Function::EnchantWithLevels {
+					levels: fields.required("levels", "number provider", provider)?,
+					treasure: fields.optional("treasure", "boolean", Value::as_bool)?
+				}
+			},
+			"set_damage" => Function::SetDamage { damage: known(&["damage"])?.required("damage", "number or {\"min\", \"max\"}", read_bounds)? },
+			"furnace_smelt" => {
+				known(&[])?;
+				Function::FurnaceSmelt
+			},
+			"limit_count" => Function::LimitCount { limit: known(&["limit"])?.required("limit", "number or range", range)?.clone() },
+			"copy_name" => Function::CopyName { source: known(&["source"])?.required("source", "string", Value::as_str)?.to_string() },
+			"copy_nbt" => {
+				let fields = known(&["source", "ops"])?;
+				Function::CopyNbt {
+					source: fields.required("source", "string", Value::as_str)?.to_string(),
+					ops: fields.required("ops", "array", Value::as_array)?.clone()
+				}
+			},
+			"copy_state" => {
+				let fields = known(&["block", "properties"])?;
+				Function::CopyState {
+					block: fields.required("block", "string", Value::as_str)?.to_string(),
+					properties: fields.required("properties", "array of strings", strings)?
+				}
+			},
+			"set_contents" => Function::SetContents { entries: known(&["entries"])?.required("entries", "array", Value::as_array)?.clone() },
+			"set_stew_effect" => Function::SetStewEffect { effects: known(&["effects"])?.required("effects", "array", Value::as_array)?.clone() },
+			"exploration_map" => {
+				let fields = known(&["destination", "decoration", "zoom", "search_radius", "skip_existing_chunks"])?;
+				Function::ExplorationMap {
+					destination: fields.optional("destination", "string", Value::as_str)?.map(string),
+					decoration: fields.optional("decoration", "string", Value::as_str)?.map(string),
+					zoom: fields.optional("zoom", "integer", integer)?,
+					search_radius: fields.optional("search_radius", "integer", integer)?,
+					skip_existing_chunks: fields.optional("skip_existing_chunks", "boolean", Value::as_bool)?
+				}
+			},
+			"set_name" => {
+				let fields = known(&["name", "entity"])?;
+				Function::SetName {
+					name: fields.required("name", "JSON text", Some)?.clone(),
+					entity: fields.optional("entity", "string", Value::as_str)?.map(string)
+				}
+			},
+			"set_lore" => {
+				let fields = known(&["lore", "entity", "replace"])?;
+				Function::SetLore {
+					lore: fields.required("lore", "array", Value::as_array)?.clone(),
+					entity: fields.optional("entity", "string", Value::as_str)?.map(string),
+					replace: fields.optional("replace", "boolean", Value::as_bool)?
+				}
+			},
+			"set_attributes" => Function::SetAttributes { modifiers: known(&["modifiers"])?.required("modifiers", "array", Value::as_array)?.clone() },
+			"fill_player_head" => Function::FillPlayerHead { entity: known(&["entity"])?.required("entity", "string", Value::as_str)?.to_string() },
+			_ => Function::Other(value.clone())
+		};
+
+		Ok(function)
+	}
+}
+
+fn provider(value: &Value) -> Option<NumberProvider> {
+	NumberProvider::from_value(value).ok()
+}
+
+/// Number or `{"min", "max"}` without type, used by `looting_enchant` and `set_damage`
+fn read_bounds(value: &Value) -> Option<NumberProvider> {
+	match provider(value)? {
+		NumberProvider::Binomial { .. } => None,
+		provider => Some(provider)
+	}
+}
+
+fn bounds(provider: &NumberProvider) -> Value {
+	match provider {
+		NumberProvider::Uniform { min, max } => {
+			let mut map = Map::new();
+			map.insert("min".to_string(), number(*min));
+			map.insert("max".to_string(), number(*max));
+			Value::Object(map)
+		},
+		provider => provider.to_value()
+	}
+}
+
+impl LootFunction {
+	/// Namespace of this function, see `Function::name()`
+	pub fn name(&self) -> Option<&str> {
+		self.function.name()
+	}
+
+	/// Convert this function into JSON value the same way vanilla loot table write it
+	pub fn to_value(&self) -> Value {
+		if let Function::Other(value) = &self.function {
+			return value.clone();
+		}
+
+		let mut map = Map::new();
+		self.function.write(&mut map);
+		if !self.conditions.is_empty() {
+			map.insert("conditions".to_string(), self.conditions.iter().map(Condition::to_value).collect());
+		}
+		if let Some(name) = self.name() {
+			map.insert("function".to_string(), Value::from(name));
+		}
+
+		Value::Object(map)
+	}
+
+	/// Interpret JSON value as loot function
+	///
+	/// This method can fail when `value` isn't an object with `function` field, when one of the conditions is invalid
+	/// or when a known function is missing a field or has a field of the wrong type.
+	pub fn from_value(value: &Value) -> Result<LootFunction, FunctionError> {
+		LootFunction::from_value_with(value, ParseOptions::default())
+	}
+
+	/// Same as `from_value()` but known function with an unknown field is only rejected when `options.strict` is set,
+	/// otherwise it's kept as `Function::Other` so the field isn't lost. Fields it does use are still validated either way.
+	/// Its conditions are read with `options` too.
+	pub fn from_value_with(value: &Value, options: ParseOptions) -> Result<LootFunction, FunctionError> {
+		match LootFunction::read(value, options, true) {
+			Err(FunctionError::UnknownField { .. }) if !options.strict => LootFunction::read(value, options, false).map(|_| Function::Other(value.clone()).into()),
+			result => result
+		}
+	}
+
+	fn read(value: &Value, options: ParseOptions, strict: bool) -> Result<LootFunction, FunctionError> {
+		let (map, name) = match value.as_object().and_then(|map| Some((map, map.get("function")?.as_str()?))) {
+			Some(result) => result,
+			None => return Err(FunctionError::Malformed(value.clone()))
+		};
+
+		let function = Function::read(name, map, value, strict)?;
+		let conditions = match (&function, map.get("conditions")) {
+			(Function::Other(_), _) | (_, None) => Vec::default(),
+			(_, Some(Value::Array(conditions))) => conditions.iter().map(|condition| Condition::from_value_with(condition, options)).collect::<Result<_, _>>()?,
+			(_, Some(_)) => return Err(FunctionError::field(name, FieldError::Invalid { field: "conditions", expected: "array of conditions" }))
+		};
+
+		Ok(LootFunction { function, conditions })
+	}
+}
+
+impl From<Function> for LootFunction {
+	fn from(function: Function) -> LootFunction {
+		LootFunction { function, conditions: Vec::default() }
+	}
+}
+
+impl Serialize for LootFunction {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.to_value().serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for LootFunction {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LootFunction, D::Error> {
+		let value = Value::deserialize(deserializer)?;
+		LootFunction::from_value(&value).map_err(|error| D::Error::custom(crate::megu::render::plain(&error)))
+	}
+}
+
+/// Error type for `LootFunction`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionError {
+	/// Emit when function is not an object with `function` field
+	Malformed(Value),
+	/// Emit when known function doesn't have a required field
+	MissingField {
+		function: String,
+		field: String
+	},
+	/// Emit when field of known function has the wrong type (i.e. `"count": "many"`)
+	InvalidField {
+		function: String,
+		field: String,
+		expected: String
+	},
+	/// Emit when known function has a field that it doesn't use (usually a typo)
+	UnknownField {
+		function: String,
+		field: String
+	},
+	/// Emit when one of the function's conditions is invalid
+	Condition(ConditionError)
+}
+
+impl FunctionError {
+	fn field(function: &str, error: FieldError) -> FunctionError {
+		let function = function.to_string();
+		match error {
+			FieldError::Unknown(field) => FunctionError::UnknownField { function, field: field.to_string() },
+			FieldError::Missing(field) => FunctionError::MissingField { function, field: field.to_string() },
+			FieldError::Invalid { field, expected } => FunctionError::InvalidField { function, field: field.to_string(), expected: expected.to_string() }
+		}
+	}
+
+	/// Namespace of the function that cause this error
+	pub fn function(&self) -> Option<&str> {
+		match self {
+			FunctionError::Malformed(_) | FunctionError::Condition(_) => None,
+			FunctionError::MissingField { function, .. } | FunctionError::InvalidField { function, .. } | FunctionError::UnknownField { function, .. } => Some(function)
+		}
+	}
+}
+
+impl From<ConditionError> for FunctionError {
+	fn from(error: ConditionError) -> FunctionError {
+		FunctionError::Condition(error)
+	}
+}
+
+use std::fmt;
+use super::render::Paint;
+impl fmt::Display for FunctionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			FunctionError::Malformed(value) => write!(f, "'{}' is not a valid function, expected object with {} field.", value.to_string().cyan(), "function".black().on_white()),
+			FunctionError::MissingField { function, field } => write!(f, "'{}' function require '{}' field.", function.cyan(), field.red()),
+			FunctionError::InvalidField { function, field, expected } => write!(f, "'{}' field of '{}' function must be {}.", field.red(), function.cyan(), expected),
+			FunctionError::UnknownField { function, field } => write!(f, "Unknown field '{}' in '{}' function.", field.red(), function.cyan()),
+			FunctionError::Condition(error) => write!(f, "{}", error)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn read(value: Value) -> Result<LootFunction, FunctionError> {
+		LootFunction::from_value_with(&value, ParseOptions::strict())
+	}
+
+	#[test]
+	fn read_known_functions() {
+		assert_eq!(read(json!({ "function": "minecraft:explosion_decay" })), Ok(Function::ExplosionDecay.into()));
+		assert_eq!(
+			read(json!({ "function": "minecraft:set_count", "count": { "n": 3, "p": 0.5, "type": "minecraft:binomial" } })),
+			Ok(Function::SetCount { count: NumberProvider::Binomial { n: 3.0, p: 0.5 } }.into())
+		);
+		assert_eq!(
+			read(json!({ "function": "set_nbt", "tag": "{Potion:\"minecraft:water\"}", "conditions": [{ "condition": "minecraft:killed_by_player" }] })),
+			Ok(LootFunction {
+				function: Function::SetNbt { tag: "{Potion:\"minecraft:water\"}".to_string() },
+				conditions: vec![Condition::KilledByPlayer { inverse: None }]
+			})
+		);
+	}
+
+	#[test]
+	fn validate_known_functions() {
+		assert_eq!(
+			read(json!({ "function": "minecraft:set_count", "cuont": 2 })),
+			Err(FunctionError::UnknownField { function: "minecraft:set_count".to_string(), field: "cuont".to_string() })
+		);
+		assert_eq!(
+			read(json!({ "function": "minecraft:looting_enchant", "count": { "n": 3, "p": 0.5, "type": "minecraft:binomial" } })),
+			Err(FunctionError::InvalidField {
+				function: "minecraft:looting_enchant".to_string(),
+				field: "count".to_string(),
+				expected: "number or {\"min\", \"max\"}".to_string()
+			})
+		);
+		assert!(matches!(read(json!({ "function": "minecraft:furnace_smelt", "conditions": [{ "condition": "minecraft:random_chance" }] })), Err(FunctionError::Condition(_))));
+		assert!(matches!(read(json!({ "count": 1 })), Err(FunctionError::Malformed(_))));
+	}
+
+	#[test]
+	fn keep_unknown_fields() {
+		let value = json!({ "function": "minecraft:set_count", "count": 2, "add": true });
+		let function = LootFunction::from_value(&value).unwrap();
+
+		assert_eq!(function, Function::Other(value.clone()).into());
+		assert_eq!(function.to_value(), value);
+		assert_eq!(
+			LootFunction::from_value(&json!({ "function": "minecraft:set_count", "count": "many", "add": true })),
+			Err(FunctionError::InvalidField { function: "minecraft:set_count".to_string(), field: "count".to_string(), expected: "number provider".to_string() })
+		);
+		assert!(matches!(LootFunction::from_value(&json!({ "function": "minecraft:set_count", "add": true })), Err(FunctionError::MissingField { .. })));
+		assert!(matches!(LootFunction::from_value(&json!({ "function": "minecraft:set_count" })), Err(FunctionError::MissingField { .. })));
+	}
+
+	#[test]
+	fn keep_unknown_functions() {
+		let value = json!({ "function": "boomber:sparkle", "conditions": [{ "chance": 1 }] });
+		let function = read(value.clone()).unwrap();
+
+		assert_eq!(function, Function::Other(value.clone()).into());
+		assert_eq!(function.name(), Some("boomber:sparkle"));
+		assert_eq!(function.to_value(), value);
+	}
+
+	#[test]
+	fn write_functions_like_vanilla() {
+		let value = json!({ "function": "minecraft:looting_enchant", "count": { "min": 0, "max": 1 }, "limit": 1 });
+		assert_eq!(read(value.clone()).unwrap().to_value(), value);
+
+		let value = json!({ "function": "minecraft:set_count", "count": { "min": 1, "max": 3, "type": "minecraft:uniform" }, "conditions": [{ "condition": "minecraft:survives_explosion" }] });
+		assert_eq!(serde_json::to_value(read(value.clone()).unwrap()).unwrap(), value);
+	}
+}
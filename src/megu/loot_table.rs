@@ -0,0 +1,236 @@
+use super::{MeguScript, MeguDrop, DropType, NumberProvider};
+use super::condition::Condition;
+use super::function::LootFunction;
+use serde_json::Value;
+
+/// Vanilla loot table structure that Minecraft can load from `data/<namespace>/loot_tables/*.json`
+///
+/// # Examples
+/// ```
+/// # use rna::{MeguScript, LootTable};
+/// let table = LootTable::from(&MeguScript::default());
+/// assert_eq!(table.to_string().unwrap(), r#"{"pools":[]}"#);
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct LootTable {
+	#[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+	pub kind: Option<String>,
+	#[serde(default)]
+	pub pools: Vec<LootPool>
+}
+
+/// A single pool inside vanilla loot table
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct LootPool {
+	pub rolls: NumberProvider,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub bonus_rolls: Option<NumberProvider>,
+	#[serde(default)]
+	pub entries: Vec<LootEntry>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub conditions: Vec<Value>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub functions: Vec<Value>
+}
+
+/// A single entry inside vanilla loot pool
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct LootEntry {
+	#[serde(rename = "type")]
+	pub kind: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub children: Vec<LootEntry>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub weight: Option<u32>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub quality: Option<i32>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub conditions: Vec<Value>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub functions: Vec<Value>
+}
+
+impl LootTable {
+	/// Serialize this loot table into JSON string
+	pub fn to_string(&self) -> Result<String, js::Error> {
+		js::to_string(self)
+	}
+
+	/// Serialize this loot table into human-readable JSON string
+	pub fn to_string_pretty(&self) -> Result<String, js::Error> {
+		js::to_string_pretty(self)
+	}
+
+	/// Write this loot table to `path`, creating parent directories when needed.
+	pub fn write(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+		let path: PathBuf = path.into();
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+
+		let content = self.to_string_pretty()?;
+		fs::write(path, content)
+	}
+}
+
+/// Lower MeguScript into vanilla loot table.
+///
+/// Every `MeguDrop` inside `pools` become its own pool with a single entry,
+/// except `rna:pool` drop which become a pool with its children as entries (and its own conditions and functions as the pool's).
+/// Drop without `rolls` will roll once.
+impl From<&MeguScript> for LootTable {
+	fn from(script: &MeguScript) -> LootTable {
+		let kind = script.kind.clone();
+		let pools = script.pools
+			.values()
+			.map(LootPool::from)
+			.collect();
+
+		LootTable { kind, pools }
+	}
+}
+impl From<MeguScript> for LootTable {
+	fn from(script: MeguScript) -> LootTable {
+		LootTable::from(&script)
+	}
+}
+
+impl From<&MeguDrop> for LootPool {
+	fn from(drop: &MeguDrop) -> LootPool {
+		let rolls = drop.rolls.clone().unwrap_or_default();
+		let bonus_rolls = drop.bonus_rolls.clone();
+
+		match drop.kind {
+			DropType::Pool => LootPool {
+				rolls,
+				bonus_rolls,
+				entries: drop.children.iter().flatten().map(LootEntry::from).collect(),
+				conditions: drop.conditions.iter().map(Condition::to_value).collect(),
+				functions: drop.functions.iter().map(LootFunction::to_value).collect()
+			},
+			_ => LootPool {
+				rolls,
+				bonus_rolls,
+				entries: vec![LootEntry::from(drop)],
+				conditions: Vec::default(),
+				functions: Vec::default()
+			}
+		}
+	}
+}
+
+impl From<&MeguDrop> for LootEntry {
+	fn from(drop: &MeguDrop) -> LootEntry {
+		let kind: DropType = drop.kind;
+		let children = drop.children
+			.iter()
+			.flatten()
+			.map(LootEntry::from)
+			.collect();
+
+		LootEntry {
+			kind: kind.name().to_string(),
+			name: drop.name.clone(),
+			children,
+			weight: drop.weight,
+			quality: drop.quality,
+			conditions: drop.conditions.iter().map(Condition::to_value).collect(),
+			functions: drop.functions.iter().map(LootFunction::to_value).collect()
+		}
+	}
+}
+
+use std::path::PathBuf;
+use std::fs;
+use std::io;
+use serde_json as js;
+use serde::{Serialize, Deserialize};
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::Namespace;
+
+	#[test]
+	fn lower_single_drop() {
+		let mut script = MeguScript {
+			kind: Some("minecraft:entity".to_string()),
+			..MeguScript::default()
+		};
+		script.pools.insert(Namespace::new("minecraft", "test"), MeguDrop::new(
+			DropType::Item,
+			Some("minecraft:emerald".to_string()),
+			None,
+			vec![Condition::KilledByPlayer { inverse: None }],
+			Vec::default(),
+			false
+		));
+
+		let value = js::to_value(LootTable::from(&script)).unwrap();
+		let expect = js::json!({
+			"type": "minecraft:entity",
+			"pools": [
+				{
+					"rolls": 1,
+					"entries": [
+						{
+							"type": "minecraft:item",
+							"name": "minecraft:emerald",
+							"conditions": [{ "condition": "minecraft:killed_by_player" }]
+						}
+					]
+				}
+			]
+		});
+
+		assert_eq!(value, expect);
+	}
+
+	#[test]
+	fn lower_nested_children() {
+		let child = MeguDrop::new(DropType::Item, Some("minecraft:stone".to_string()), None, Vec::default(), Vec::default(), false);
+		let drop = MeguDrop::new(DropType::Alternatives, None, Some(vec![child]), Vec::default(), Vec::default(), true);
+
+		let entry = LootEntry::from(&drop);
+		assert_eq!(entry.kind, "minecraft:alternatives");
+		assert_eq!(entry.children.len(), 1);
+		assert_eq!(entry.children[0].name, Some("minecraft:stone".to_string()));
+	}
+
+	#[test]
+	fn lower_rolls_and_weight() {
+		let mut drop = MeguDrop::new(DropType::Item, Some("minecraft:diamond".to_string()), None, Vec::default(), Vec::default(), false);
+		drop.rolls = Some(NumberProvider::Uniform { min: 1.0, max: 3.0 });
+		drop.weight = Some(10);
+
+		let value = js::to_value(LootPool::from(&drop)).unwrap();
+		let expect = js::json!({
+			"rolls": { "min": 1, "max": 3, "type": "minecraft:uniform" },
+			"entries": [{ "type": "minecraft:item", "name": "minecraft:diamond", "weight": 10 }]
+		});
+
+		assert_eq!(value, expect);
+	}
+
+	#[test]
+	fn lower_pool_drop_into_single_pool() {
+		let entries = ["minecraft:iron_ingot", "minecraft:carrot"].iter()
+			.map(|name| MeguDrop::new(DropType::Item, Some(name.to_string()), None, Vec::default(), Vec::default(), false))
+			.collect::<Vec<_>>();
+		let drop = MeguDrop::new(DropType::Pool, None, Some(entries), vec![Condition::KilledByPlayer { inverse: None }], Vec::default(), true);
+
+		let value = js::to_value(LootPool::from(&drop)).unwrap();
+		let expect = js::json!({
+			"rolls": 1,
+			"entries": [
+				{ "type": "minecraft:item", "name": "minecraft:iron_ingot" },
+				{ "type": "minecraft:item", "name": "minecraft:carrot" }
+			],
+			"conditions": [{ "condition": "minecraft:killed_by_player" }]
+		});
+
+		assert_eq!(value, expect);
+	}
+}
@@ -2,13 +2,43 @@ pub(crate) mod script;
 pub(crate) mod drop;
 pub(crate) mod namespace;
 pub(crate) mod extension;
+pub(crate) mod loot_table;
+pub(crate) mod decompile;
+pub(crate) mod number;
+pub(crate) mod vanilla;
+pub(crate) mod resolver;
+pub(crate) mod conflict;
+pub(crate) mod diagnostic;
+pub(crate) mod pattern;
+pub(crate) mod builder;
+pub(crate) mod datapack;
+pub(crate) mod detect;
+pub(crate) mod span;
+pub(crate) mod code;
+pub(crate) mod render;
+pub(crate) mod condition;
+pub(crate) mod fields;
+pub(crate) mod function;
+#[cfg(test)]
+pub(crate) mod temp;
 
-pub use script::{MeguScript, ReadError};
+pub use script::{MeguScript, ReadError, ParseOptions};
 pub use drop::{MeguDrop, DropType, DropFormat};
 pub use namespace::{Namespace, DecodeError};
 pub use extension::{Extension, ExtensionError};
+pub use resolver::Resolver;
+pub use conflict::{ConflictPolicy, Conflict, ScriptSource};
+pub use diagnostic::{Diagnostics, Warning};
+pub use pattern::{Pattern, Removal};
+pub use loot_table::LootTable;
+pub use number::NumberProvider;
+pub use condition::Condition;
+pub use function::LootFunction;
+pub use detect::Detector;
+pub use span::Span;
 
 use std::path::PathBuf;
+use std::collections::HashMap;
 /// Shorthand for defining a `Result` that can fail with `MeguError` type
 pub type MeguResult<T> = Result<T, MeguError>;
 
@@ -19,7 +49,14 @@ pub type MeguResult<T> = Result<T, MeguError>;
 /// - File does not exists
 /// - Path is a directory
 /// - There is syntax error inside the file
-pub fn interpret_file(path: impl Into<PathBuf>, base_path: impl Into<PathBuf>) -> MeguResult<MeguScript> {
+pub fn interpret_file(path: impl Into<PathBuf>, resolver: impl Into<Resolver>) -> MeguResult<MeguScript> {
+	interpret_file_with(path, resolver, ParseOptions::default())
+}
+
+/// Same as `interpret_file()` but with the given `ParseOptions`.
+/// 
+/// Use `ParseOptions::strict()` to reject unknown fields.
+pub fn interpret_file_with(path: impl Into<PathBuf>, resolver: impl Into<Resolver>, options: ParseOptions) -> MeguResult<MeguScript> {
 	let path: PathBuf = path.into();
 	if !path.exists() {
 		return Err(MeguError::NotExist(path));
@@ -29,7 +66,7 @@ pub fn interpret_file(path: impl Into<PathBuf>, base_path: impl Into<PathBuf>) -
 		return Err(MeguError::NotAFile(path));
 	}
 
-	let result = match MeguScript::from_path(&path, base_path) {
+	let result = match MeguScript::from_path_with(&path, resolver, options) {
 		Ok(result) => result,
 		Err(error) => return Err(MeguError::Read((path, error)))
 	};
@@ -46,12 +83,25 @@ pub enum MeguError {
 	NotAFile(PathBuf),
 	/// Emit when file contain syntax error
 	Read((PathBuf, ReadError)),
-	/// `MeguError::Read` but emit in `merge()` function
-	Merge(ReadError)
+	/// `MeguError::Read` but emit in `merge()` function, along with the file that cause it (i.e. a script in `extend` chain).
+	/// The path is `None` when the script wasn't read from a file.
+	Merge((Option<PathBuf>, ReadError)),
+	/// Emit in `merge_with()` when two scripts define the same pool key with `ConflictPolicy::Error`
+	Conflict(Conflict)
+}
+
+impl MeguError {
+	/// Where this error is inside the script (if known)
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			MeguError::Read((_, error)) | MeguError::Merge((_, error)) => error.span(),
+			_ => None
+		}
+	}
 }
 
 use std::fmt;
-use colored::*;
+use self::render::Paint;
 use std::error::Error;
 impl From<(PathBuf, ReadError)> for MeguError {
 	fn from(error: (PathBuf, ReadError)) -> MeguError {
@@ -65,27 +115,124 @@ impl fmt::Display for MeguError {
 			MeguError::NotExist(path) => write!(f, "'{}' does not exists", path.display().to_string().cyan()),
 			MeguError::NotAFile(path) => write!(f, "'{}' is not a file", path.display().to_string().cyan()),
 			MeguError::Read((path, error)) => write!(f, "[{}] {}", path.display().to_string().green(), error),
-			MeguError::Merge(error) => write!(f, "{}", error)
+			MeguError::Merge((Some(path), error)) => write!(f, "[{}] {}", path.display().to_string().green(), error),
+			MeguError::Merge((None, error)) => write!(f, "{}", error),
+			MeguError::Conflict(conflict) => write!(f, "{}", conflict)
 		}
 	}
 }
 
-/// Merge MeguScripts together.
-pub fn merge(scripts: &[MeguScript], base_path: impl Into<PathBuf>) -> MeguResult<MeguScript> {
+/// Merge MeguScripts together, along with every warning that come up while merging.
+/// 
+/// `resolver` can be a single `base_path` or a `Resolver` with multiple roots.
+/// When two scripts define the same pool key, the later script win and `Warning::Conflict` is reported.
+/// Use `merge_with()` to choose another `ConflictPolicy`.
+///
+/// # Examples
+/// ```
+/// # use rna::{MeguScript, utils};
+/// # use rna::diagnostic::Warning;
+/// # use rna::script::ScriptFormat;
+/// let ruby: ScriptFormat = serde_json::from_str(r#"{"pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:ruby"}}}"#).unwrap();
+/// let sapphire: ScriptFormat = serde_json::from_str(r#"{"pools":{"boomber:gem":{"type":"minecraft:item","name":"boomber:sapphire"}}}"#).unwrap();
+///
+/// let (merged, warnings) = utils::merge(&[MeguScript::from(ruby), MeguScript::from(sapphire)], "resource").unwrap();
+/// assert_eq!(merged.pools().len(), 1);
+/// assert!(matches!(warnings.warnings(), [Warning::Conflict(_)]));
+/// ```
+pub fn merge(scripts: &[MeguScript], resolver: impl Into<Resolver>) -> MeguResult<(MeguScript, Diagnostics)> {
+	let mut diagnostics = Diagnostics::default();
+	let result = merge_with(scripts, resolver, &MergeOptions::default(), &mut diagnostics)?;
+	Ok((result, diagnostics))
+}
+
+/// Options for `merge_with()` function
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MergeOptions {
+	/// What to do when two scripts define the same pool key with different drop, `ConflictPolicy::Warn` by default
+	pub conflict_policy: ConflictPolicy,
+	/// Sort pools by key instead of keeping the order they were merged in
+	pub sort_pools: bool,
+	/// Options to read the scripts in the `extend` chain of each script with
+	pub parse_options: ParseOptions
+}
+
+/// Merge MeguScripts together, any warning will be pushed into `diagnostics`.
+/// 
+/// Every pattern in `remove` field that doesn't match any drop is reported as `Warning::UnmatchedRemove`,
+/// along with the script in the `extend` chain that declared it.
+/// 
+/// Scripts are merged in slice order and pools keep the order they were first defined in.
+/// Two scripts defining the same pool key with identical drop is not a conflict.
+/// 
+/// # Errors
+/// This method can fail when:
+/// - One of the scripts cannot be compiled
+/// - Two scripts define the same pool key and `conflict_policy` is `ConflictPolicy::Error`
+pub fn merge_with(scripts: &[MeguScript], resolver: impl Into<Resolver>, options: &MergeOptions, diagnostics: &mut Diagnostics) -> MeguResult<MeguScript> {
+	let parse_options = vec![options.parse_options; scripts.len()];
+	let (result, _) = merge_tracked(scripts, &parse_options, &resolver.into(), options, diagnostics)?;
+	Ok(result)
+}
+
+/// Same as `merge_with()` but also return the index of the script that supplied each pool.
+///
+/// `extend` chain of each script is read with its own `ParseOptions` from `parse_options` instead of `options.parse_options`.
+pub(crate) fn merge_tracked(scripts: &[MeguScript], parse_options: &[ParseOptions], resolver: &Resolver, options: &MergeOptions, diagnostics: &mut Diagnostics) -> MeguResult<(MeguScript, HashMap<Namespace, usize>)> {
 	let mut result: MeguScript = MeguScript::default();
-	let base_path = base_path.into();
+	let mut owners: HashMap<Namespace, usize> = HashMap::default();
+	let mut requesters: Vec<(usize, Option<Extension>)> = Vec::default();
+
+	let source = |index: usize| ScriptSource {
+		index,
+		path: scripts[index].source.clone()
+	};
 
-	for script in scripts {
-		let script = match script.compile(&base_path) {
-			Ok(script) => script,
+	for (index, script) in scripts.iter().enumerate() {
+		let (script, origins) = match script.compile_traced(resolver, parse_options[index]) {
+			Ok(result) => result,
 			Err(error) => return Err(MeguError::Merge(error))
 		};
-		script.merge(&mut result);
+
+		if result.kind.is_none() {
+			result.kind = script.kind.clone();
+		}
+
+		for (key, value) in &script.pools {
+			if let Some(&owner) = owners.get(key) {
+				if result.pools.get(key) == Some(value) {
+					continue;
+				}
+
+				let conflict = Conflict { key: key.clone(), first: source(owner), second: source(index) };
+				match options.conflict_policy {
+					ConflictPolicy::Error => return Err(MeguError::Conflict(conflict)),
+					ConflictPolicy::Warn => diagnostics.push(Warning::Conflict(conflict)),
+					ConflictPolicy::KeepFirst => continue,
+					ConflictPolicy::KeepLast => ()
+				}
+			}
+
+			result.pools.insert(key.clone(), value.clone());
+			owners.insert(key.clone(), index);
+		}
+
+		result.remove.extend(script.remove.iter().cloned());
+		requesters.extend(origins.into_iter().map(|origin| (index, origin)));
 	}
 
-	result.remove_drops();
+	let removals = result.remove_drops();
+	for (removal, (index, extend)) in removals.into_iter().zip(requesters) {
+		if removal.removed.is_empty() {
+			diagnostics.push(Warning::UnmatchedRemove { pattern: removal.pattern, script: source(index), extend });
+		}
+	}
+	if options.sort_pools {
+		result.sort_pools();
+	}
 
-	Ok(result)
+	owners.retain(|key, _| result.pools.contains_key(key));
+	Ok((result, owners))
 }
 
 #[cfg(test)]
@@ -98,4 +245,96 @@ mod tests {
 		let path = PathBuf::from("/this/path/should/never/exists/EVER");
 		interpret_file(&path, "resource").unwrap();
 	}
-}
\ No newline at end of file
+
+	fn drop_script(name: &str) -> MeguScript {
+		let value = format!(r#"{{"pools":{{"boomber:ruby":{{"type":"minecraft:item","name":"{}"}}}}}}"#, name);
+		let format: script::ScriptFormat = serde_json::from_str(&value).unwrap();
+		MeguScript::from(format)
+	}
+
+	fn merge_ruby(policy: ConflictPolicy, diagnostics: &mut Diagnostics) -> MeguResult<MeguScript> {
+		let scripts = [drop_script("boomber:ruby"), drop_script("boomber:ruby_shard")];
+		let options = MergeOptions { conflict_policy: policy, ..MergeOptions::default() };
+		merge_with(&scripts, "resource", &options, diagnostics)
+	}
+
+	#[test]
+	fn merge_conflict_policy() {
+		let key = Namespace::new("boomber", "ruby");
+		let name = |script: &MeguScript| script.pools[&key].name.clone().unwrap();
+		let mut diagnostics = Diagnostics::default();
+
+		assert_eq!(name(&merge_ruby(ConflictPolicy::KeepFirst, &mut diagnostics).unwrap()), "boomber:ruby");
+		assert_eq!(name(&merge_ruby(ConflictPolicy::KeepLast, &mut diagnostics).unwrap()), "boomber:ruby_shard");
+		assert!(diagnostics.is_empty());
+
+		assert_eq!(name(&merge_ruby(ConflictPolicy::Warn, &mut diagnostics).unwrap()), "boomber:ruby_shard");
+		assert_eq!(diagnostics.len(), 1);
+
+		match merge_ruby(ConflictPolicy::Error, &mut diagnostics) {
+			Err(MeguError::Conflict(conflict)) => {
+				assert_eq!(conflict.key, key);
+				assert_eq!(conflict.first.index, 0);
+				assert_eq!(conflict.second.index, 1);
+			},
+			_ => panic!("expect conflict error")
+		}
+	}
+
+	#[test]
+	fn identical_drops_are_not_conflict() {
+		let scripts = [drop_script("boomber:ruby"), drop_script("boomber:ruby")];
+		let options = MergeOptions { conflict_policy: ConflictPolicy::Error, ..MergeOptions::default() };
+		assert!(merge_with(&scripts, "resource", &options, &mut Diagnostics::default()).is_ok());
+	}
+
+	#[test]
+	fn report_unmatched_remove() {
+		let value = r#"{"pools":{},"remove":["boomber:ruby","boomber:sapphire/*"]}"#;
+		let format: script::ScriptFormat = serde_json::from_str(value).unwrap();
+		let scripts = [drop_script("boomber:ruby"), MeguScript::from(format)];
+		let mut diagnostics = Diagnostics::default();
+
+		let result = merge_with(&scripts, "resource", &MergeOptions::default(), &mut diagnostics).unwrap();
+		assert!(result.pools.is_empty());
+		assert_eq!(diagnostics.warnings(), [Warning::UnmatchedRemove {
+			pattern: Pattern::decode("boomber:sapphire/*").unwrap(),
+			script: ScriptSource { index: 1, path: None },
+			extend: None
+		}]);
+	}
+
+	#[test]
+	fn report_unmatched_remove_where_it_was_declared() {
+		let root = temp::TempDir::new("inherited-remove");
+		let base = root.write("boomber/base.ult", r#"{"pools":{},"remove":["boomber:sapphire"]}"#);
+		root.write("boomber/parent.ult", r#"{"extend":"boomber:base","pools":{}}"#);
+
+		let value = r#"{"extend":"boomber:parent","pools":{},"remove":["boomber:emerald"]}"#;
+		let format: script::ScriptFormat = serde_json::from_str(value).unwrap();
+		let script = MeguScript::from_script_format(format, root.path()).unwrap();
+
+		let (_, diagnostics) = merge(&[script], root.path()).unwrap();
+		match diagnostics.warnings() {
+			[Warning::UnmatchedRemove { pattern: inherited, extend: Some(extension), .. }, Warning::UnmatchedRemove { pattern: own, extend: None, script }] => {
+				assert_eq!(inherited.as_str(), "boomber:sapphire");
+				assert_eq!(extension.location(), base.as_path());
+				assert_eq!(own.as_str(), "boomber:emerald");
+				assert_eq!(script.index, 0);
+			},
+			warnings => panic!("expect two unmatched remove, found {:?}", warnings)
+		}
+	}
+
+	#[test]
+	fn overlapping_remove_are_matched() {
+		let value = r#"{"pools":{},"remove":["boomber:ruby","boomber:*"]}"#;
+		let format: script::ScriptFormat = serde_json::from_str(value).unwrap();
+		let scripts = [drop_script("boomber:ruby"), MeguScript::from(format.clone()), MeguScript::from(format)];
+		let mut diagnostics = Diagnostics::default();
+
+		let result = merge_with(&scripts, "resource", &MergeOptions::default(), &mut diagnostics).unwrap();
+		assert!(result.pools.is_empty());
+		assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+	}
+}
@@ -25,7 +25,7 @@
 /// assert_eq!(namespace.prefix, "megumin");
 /// assert_eq!(namespace.suffix, "explosion");
 /// ```
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Namespace {
 	/// String that come before `:`
 	pub prefix: String,
@@ -71,7 +71,7 @@ impl Namespace {
 	/// ```
 	pub fn decode(value: impl Into<String>) -> Result<Namespace, DecodeError> {
 		let value = value.into();
-		let namespace_validation = Regex::new(&NAMESPACE_RULE)?;
+		let namespace_validation = Regex::new(NAMESPACE_RULE)?;
 
 		if !namespace_validation.is_match(&value) {
 			return Err(DecodeError::InvalidNamespace(value));
@@ -112,7 +112,7 @@ pub enum DecodeError {
 	TooManyColons(String)
 }
 
-use colored::*;
+use super::render::Paint;
 /// Create Namespace from &str.  
 /// This will `unwrap()` error emit from `Namespace::decode()` function.
 impl From<&str> for Namespace {
@@ -141,6 +141,11 @@ impl fmt::Debug for Namespace {
 		write!(f, "{}:{}", self.prefix, self.suffix)
 	}
 }
+impl fmt::Display for Namespace {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}:{}", self.prefix, self.suffix)
+	}
+}
 
 #[cfg(test)]
 mod tests {
@@ -160,7 +165,7 @@ mod tests {
 	#[test]
 	#[should_panic]
 	fn panic_on_invalid_namespace() {
-		Namespace::from("this:namespace:IS invalid");
+		let _ = Namespace::from("this:namespace:IS invalid");
 	}
 
 	#[test]
@@ -177,6 +182,6 @@ mod tests {
 	#[test]
 	#[should_panic]
 	fn panic_on_invalid_namespace_without_prefix() {
-		Namespace::from("This Namespace Is Not Valid");
+		let _ = Namespace::from("This Namespace Is Not Valid");
 	}
 }
\ No newline at end of file
@@ -0,0 +1,175 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::{Value, Map};
+
+/// Number provider used in `rolls`, `bonus_rolls` and loot functions.
+///
+/// # Examples
+/// ```
+/// # use rna::number::NumberProvider;
+/// let rolls: NumberProvider = serde_json::from_str("1").unwrap();
+/// assert_eq!(rolls, NumberProvider::Constant(1.0));
+///
+/// let rolls: NumberProvider = serde_json::from_str(r#"{"min":0,"max":2,"type":"minecraft:uniform"}"#).unwrap();
+/// assert_eq!(rolls, NumberProvider::Uniform { min: 0.0, max: 2.0 });
+///
+/// let rolls: NumberProvider = serde_json::from_str(r#"{"type":"minecraft:score","target":"this","score":"rolls"}"#).unwrap();
+/// assert!(matches!(rolls, NumberProvider::Other(_)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberProvider {
+	/// A fixed number (i.e. `1`)
+	Constant(f64),
+	/// Random number between `min` and `max` (i.e. `{"min": 0, "max": 2, "type": "minecraft:uniform"}`)
+	Uniform { min: f64, max: f64 },
+	/// Binomial distribution with `n` tries and `p` probability (i.e. `{"n": 3, "p": 0.5, "type": "minecraft:binomial"}`)
+	Binomial { n: f64, p: f64 },
+	/// Number provider that this library doesn't know (i.e. `minecraft:score`) or that contain another number provider, kept as it is
+	Other(Value)
+}
+
+impl Default for NumberProvider {
+	fn default() -> NumberProvider {
+		NumberProvider::Constant(1.0)
+	}
+}
+
+impl NumberProvider {
+	/// Convert this number provider into JSON value the same way vanilla loot table write it
+	pub fn to_value(&self) -> Value {
+		match *self {
+			NumberProvider::Constant(value) => number(value),
+			NumberProvider::Other(ref value) => value.clone(),
+			NumberProvider::Uniform { min, max } => {
+				let mut map = Map::new();
+				map.insert("min".to_string(), number(min));
+				map.insert("max".to_string(), number(max));
+				map.insert("type".to_string(), Value::from("minecraft:uniform"));
+				Value::Object(map)
+			},
+			NumberProvider::Binomial { n, p } => {
+				let mut map = Map::new();
+				map.insert("n".to_string(), number(n));
+				map.insert("p".to_string(), number(p));
+				map.insert("type".to_string(), Value::from("minecraft:binomial"));
+				Value::Object(map)
+			}
+		}
+	}
+
+	/// Interpret JSON value as number provider
+	///
+	/// Object without `type` is uniform when it has `min` or `max`, anything this library doesn't understand is kept as `Other`.
+	pub fn from_value(value: &Value) -> Result<NumberProvider, String> {
+		if let Some(value) = value.as_f64() {
+			return Ok(NumberProvider::Constant(value));
+		}
+
+		let map = match value.as_object() {
+			Some(map) => map,
+			None => return Err(format!("expected number or object, found '{}'", value))
+		};
+
+		// `None` when the field is another number provider
+		let field = |name: &str| -> Result<Option<f64>, String> {
+			match map.get(name) {
+				Some(Value::Number(value)) => Ok(value.as_f64()),
+				Some(Value::Object(_)) => Ok(None),
+				Some(value) => Err(format!("expected number or object in field '{}', found '{}'", name, value)),
+				None => Err(format!("missing number field '{}'", name))
+			}
+		};
+		let other = || NumberProvider::Other(value.clone());
+
+		let kind = match map.get("type") {
+			Some(kind) => kind.as_str().ok_or_else(|| format!("'{}' is not a valid number provider type", kind))?,
+			None if map.contains_key("min") || map.contains_key("max") => "minecraft:uniform",
+			None => return Ok(other())
+		};
+		let provider = match kind.trim_start_matches("minecraft:") {
+			"constant" => field("value")?.map(NumberProvider::Constant),
+			"uniform" => field("min")?.zip(field("max")?).map(|(min, max)| NumberProvider::Uniform { min, max }),
+			"binomial" => field("n")?.zip(field("p")?).map(|(n, p)| NumberProvider::Binomial { n, p }),
+			_ => None
+		};
+
+		Ok(provider.unwrap_or_else(other))
+	}
+}
+
+/// Deserialize number provider as JSON value, failing when it isn't a valid number provider
+pub(crate) fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+	let value = Option::<Value>::deserialize(deserializer)?;
+	if let Some(value) = &value {
+		NumberProvider::from_value(value).map_err(D::Error::custom)?;
+	}
+
+	Ok(value)
+}
+
+/// Write whole number as integer so `1` doesn't turn into `1.0`
+pub(crate) fn number(value: f64) -> Value {
+	if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+		Value::from(value as i64)
+	}
+	else {
+		Value::from(value)
+	}
+}
+
+impl Serialize for NumberProvider {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.to_value().serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for NumberProvider {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<NumberProvider, D::Error> {
+		let value = Value::deserialize(deserializer)?;
+		NumberProvider::from_value(&value).map_err(D::Error::custom)
+	}
+}
+
+impl From<f64> for NumberProvider {
+	fn from(value: f64) -> NumberProvider {
+		NumberProvider::Constant(value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json as js;
+
+	#[test]
+	fn read_number_providers() {
+		assert_eq!(js::from_str::<NumberProvider>("2.5").unwrap(), NumberProvider::Constant(2.5));
+		assert_eq!(js::from_str::<NumberProvider>(r#"{"min":0,"max":1}"#).unwrap(), NumberProvider::Uniform { min: 0.0, max: 1.0 });
+		assert_eq!(js::from_str::<NumberProvider>(r#"{"n":3,"p":0.5,"type":"minecraft:binomial"}"#).unwrap(), NumberProvider::Binomial { n: 3.0, p: 0.5 });
+		assert!(js::from_str::<NumberProvider>(r#"{"min":0}"#).is_err());
+		assert!(js::from_str::<NumberProvider>(r#"{"min":"0","max":1}"#).is_err());
+		assert!(js::from_str::<NumberProvider>("true").is_err());
+	}
+
+	#[test]
+	fn keep_other_number_providers() {
+		let other = |value: Value| {
+			let provider = NumberProvider::from_value(&value).unwrap();
+			assert_eq!(provider.to_value(), value);
+			matches!(provider, NumberProvider::Other(_))
+		};
+
+		assert!(other(js::json!({ "type": "minecraft:score", "target": "this", "score": "rolls" })));
+		assert!(other(js::json!({ "type": "minecraft:uniform", "min": 1, "max": { "type": "minecraft:binomial", "n": 3, "p": 0.5 } })));
+		assert!(other(js::json!({ "n": 3, "p": 0.5 })));
+	}
+
+	#[test]
+	fn write_whole_number_as_integer() {
+		assert_eq!(js::to_string(&NumberProvider::Constant(1.0)).unwrap(), "1");
+		assert_eq!(
+			js::to_value(NumberProvider::Uniform { min: 1.0, max: 2.5 }).unwrap(),
+			js::json!({ "min": 1, "max": 2.5, "type": "minecraft:uniform" })
+		);
+	}
+}
@@ -0,0 +1,156 @@
+use super::{Namespace, DecodeError, MeguDrop};
+use regex::Regex;
+
+/// Namespace pattern used inside `remove` field.
+///
+/// Pattern follow the same rule as `Namespace` with these wildcards:
+/// - `*` match anything except `/`
+/// - `**` match anything including `/`
+/// - `?` match a single character except `/`
+///
+/// # Examples
+/// ```
+/// # use rna::Namespace;
+/// # use rna::pattern::Pattern;
+/// let pattern = Pattern::decode("minecraft:minecraft/entities/zombie/*").unwrap();
+/// assert!(pattern.is_match(&Namespace::new("minecraft", "minecraft/entities/zombie/carrot")));
+/// assert!(!pattern.is_match(&Namespace::new("minecraft", "minecraft/entities/zombie")));
+/// ```
+#[derive(Clone)]
+pub struct Pattern {
+	value: String,
+	regex: Regex
+}
+
+const PATTERN_RULE: &str = r#"^[a-z:._\-/\d*?]+$"#;
+
+impl Pattern {
+	/// Create Pattern from a given string.
+	///
+	/// If no colon is provided, 'minecraft' prefix will be used.
+	///
+	/// # Errors
+	/// This method can error when:
+	/// - Input contain invalid characters for pattern
+	/// - Input contain too many colons (`:`)
+	/// - There's an error inside `regex` crate
+	pub fn decode(value: impl Into<String>) -> Result<Pattern, DecodeError> {
+		let value = value.into();
+		let validation = Regex::new(PATTERN_RULE)?;
+
+		if !validation.is_match(&value) {
+			return Err(DecodeError::InvalidNamespace(value));
+		}
+
+		let value = match value.chars().filter(|&c| c == ':').count() {
+			0 => format!("minecraft:{}", value),
+			1 => value,
+			_ => return Err(DecodeError::TooManyColons(value))
+		};
+
+		let regex = Regex::new(&Pattern::translate(&value))?;
+		Ok(Pattern { value, regex })
+	}
+
+	/// Convert glob syntax into regular expression
+	fn translate(value: &str) -> String {
+		let mut result = String::from("^");
+		let mut chars = value.chars().peekable();
+
+		while let Some(c) = chars.next() {
+			match c {
+				'*' if chars.peek() == Some(&'*') => {
+					chars.next();
+					result.push_str(".*");
+				},
+				'*' => result.push_str("[^/]*"),
+				'?' => result.push_str("[^/]"),
+				c => result.push_str(&regex::escape(&c.to_string()))
+			}
+		}
+
+		result.push('$');
+		result
+	}
+
+	/// Check if `namespace` match this pattern
+	pub fn is_match(&self, namespace: &Namespace) -> bool {
+		self.regex.is_match(&namespace.to_string())
+	}
+
+	/// Check if this pattern doesn't contain any wildcard
+	pub fn is_exact(&self) -> bool {
+		!self.value.contains(['*', '?'])
+	}
+
+	/// Pattern in `prefix:suffix` form
+	pub fn as_str(&self) -> &str {
+		&self.value
+	}
+}
+
+/// Drops that was removed by a single pattern in `remove` field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Removal {
+	/// Pattern that remove the drops
+	pub pattern: Pattern,
+	/// Every drop that was removed along with its key
+	pub removed: Vec<(Namespace, MeguDrop)>
+}
+
+impl From<Namespace> for Pattern {
+	fn from(namespace: Namespace) -> Pattern {
+		let value = namespace.to_string();
+		let regex = Regex::new(&format!("^{}$", regex::escape(&value))).expect("escaped namespace is always valid regex");
+		Pattern { value, regex }
+	}
+}
+
+impl PartialEq for Pattern {
+	fn eq(&self, other: &Pattern) -> bool {
+		self.value == other.value
+	}
+}
+impl Eq for Pattern {}
+
+use std::fmt;
+impl fmt::Debug for Pattern {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.value)
+	}
+}
+impl fmt::Display for Pattern {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn match_wildcards() {
+		let single = Pattern::decode("minecraft/entities/*/carrot").unwrap();
+		assert!(single.is_match(&Namespace::new("minecraft", "minecraft/entities/zombie/carrot")));
+		assert!(!single.is_match(&Namespace::new("minecraft", "minecraft/entities/zombie/villager/carrot")));
+
+		let deep = Pattern::decode("minecraft:minecraft/entities/**").unwrap();
+		assert!(deep.is_match(&Namespace::new("minecraft", "minecraft/entities/zombie/villager/carrot")));
+		assert!(!deep.is_match(&Namespace::new("boomber", "minecraft/entities/zombie")));
+	}
+
+	#[test]
+	fn exact_pattern_match_only_itself() {
+		let pattern = Pattern::decode("boomber:ruby").unwrap();
+		assert!(pattern.is_exact());
+		assert!(pattern.is_match(&Namespace::new("boomber", "ruby")));
+		assert!(!pattern.is_match(&Namespace::new("boomber", "ruby_ore")));
+	}
+
+	#[test]
+	fn reject_invalid_pattern() {
+		assert_eq!(Pattern::decode("a:b:*"), Err(DecodeError::TooManyColons("a:b:*".to_string())));
+		assert_eq!(Pattern::decode("Boomber:*"), Err(DecodeError::InvalidNamespace("Boomber:*".to_string())));
+	}
+}
@@ -0,0 +1,395 @@
+use std::cell::Cell;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Whether `Display` of errors and warnings contain terminal colors
+///
+/// The library default is `Plain` so messages never contain escapes unless asked,
+/// command-line tools usually want `set_color_mode(ColorMode::Auto)` at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+	/// Never write ANSI escapes
+	#[default]
+	Plain,
+	/// Always write ANSI escapes
+	Ansi,
+	/// Write ANSI escapes when stderr is a terminal, respect `NO_COLOR` and `CLICOLOR_FORCE`.
+	/// The environment is only checked once, the first time it's needed.
+	Auto
+}
+
+impl ColorMode {
+	fn from_u8(value: u8) -> ColorMode {
+		match value {
+			1 => ColorMode::Ansi,
+			2 => ColorMode::Auto,
+			_ => ColorMode::Plain
+		}
+	}
+
+	fn to_u8(self) -> u8 {
+		match self {
+			ColorMode::Plain => 0,
+			ColorMode::Ansi => 1,
+			ColorMode::Auto => 2
+		}
+	}
+
+	/// Check if this mode write ANSI escapes in the current environment
+	pub fn is_enabled(self) -> bool {
+		match self {
+			ColorMode::Plain => false,
+			ColorMode::Ansi => true,
+			ColorMode::Auto => *AUTO.get_or_init(detect)
+		}
+	}
+}
+
+static GLOBAL: AtomicU8 = AtomicU8::new(0);
+static AUTO: OnceLock<bool> = OnceLock::new();
+
+thread_local! {
+	static OVERRIDE: Cell<Option<ColorMode>> = const { Cell::new(None) };
+}
+
+/// Decide `ColorMode::Auto` from environment variables and stderr
+fn detect() -> bool {
+	use std::env;
+	use std::io::IsTerminal;
+
+	let set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0");
+	if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+		return false;
+	}
+	if set("CLICOLOR_FORCE") {
+		return true;
+	}
+
+	env::var_os("CLICOLOR").is_none_or(|value| value != "0") && std::io::stderr().is_terminal()
+}
+
+/// Set color mode for every thread, `ColorMode::Plain` by default
+pub fn set_color_mode(mode: ColorMode) {
+	GLOBAL.store(mode.to_u8(), Ordering::Relaxed);
+}
+
+/// Color mode used by `Display` right now, `render()` take priority over `set_color_mode()`
+pub fn color_mode() -> ColorMode {
+	OVERRIDE
+		.with(Cell::get)
+		.unwrap_or_else(|| ColorMode::from_u8(GLOBAL.load(Ordering::Relaxed)))
+}
+
+/// Format `value` with the given color mode, regardless of `set_color_mode()`
+///
+/// # Examples
+/// ```
+/// # use rna::render::{self, ColorMode};
+/// # use rna::error::DropTypeError;
+/// let error = DropTypeError::InvalidType("minecraft:itme".to_string());
+///
+/// assert_eq!(render::render(&error, ColorMode::Plain), "'minecraft:itme' is not a valid type name.");
+/// assert_eq!(render::render(&error, ColorMode::Ansi), "'\u{1b}[36mminecraft:itme\u{1b}[0m' is not a valid type name.");
+/// ```
+pub fn render(value: &impl fmt::Display, mode: ColorMode) -> String {
+	struct Restore(Option<ColorMode>);
+	impl Drop for Restore {
+		fn drop(&mut self) {
+			OVERRIDE.with(|cell| cell.set(self.0));
+		}
+	}
+
+	let _restore = Restore(OVERRIDE.with(|cell| cell.replace(Some(mode))));
+	value.to_string()
+}
+
+/// Shorthand for `render(value, ColorMode::Plain)`
+pub fn plain(value: &impl fmt::Display) -> String {
+	render(value, ColorMode::Plain)
+}
+
+/// ANSI color code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Color {
+	Black = 0,
+	Red = 1,
+	Green = 2,
+	Yellow = 3,
+	Blue = 4,
+	Cyan = 6,
+	White = 7
+}
+
+/// Value with terminal style, only written when `color_mode()` is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Painted<T> {
+	value: T,
+	foreground: Option<Color>,
+	background: Option<Color>,
+	bold: bool
+}
+
+macro_rules! colors {
+	($($name:ident $background:ident => $color:ident),*) => {
+		impl<T> Painted<T> {
+			/// Wrap `value` without any style, chain color methods to style it
+			pub fn new(value: T) -> Painted<T> {
+				Painted { value, foreground: None, background: None, bold: false }
+			}
+
+			$(
+				pub fn $name(mut self) -> Painted<T> {
+					self.foreground = Some(Color::$color);
+					self
+				}
+
+				pub fn $background(mut self) -> Painted<T> {
+					self.background = Some(Color::$color);
+					self
+				}
+			)*
+
+			pub fn bold(mut self) -> Painted<T> {
+				self.bold = true;
+				self
+			}
+		}
+
+		/// Style any `Display` value with terminal colors that follow `color_mode()`
+		#[allow(dead_code)] // not every color is used inside the crate
+		pub(crate) trait Paint: fmt::Display {
+			fn paint(&self) -> Painted<&Self> {
+				Painted::new(self)
+			}
+
+			$(
+				fn $name(&self) -> Painted<&Self> {
+					self.paint().$name()
+				}
+
+				fn $background(&self) -> Painted<&Self> {
+					self.paint().$background()
+				}
+			)*
+
+			fn bold(&self) -> Painted<&Self> {
+				self.paint().bold()
+			}
+		}
+	};
+}
+
+colors! {
+	black on_black => Black,
+	red on_red => Red,
+	green on_green => Green,
+	yellow on_yellow => Yellow,
+	blue on_blue => Blue,
+	cyan on_cyan => Cyan,
+	white on_white => White
+}
+
+impl<T: fmt::Display + ?Sized> Paint for T {}
+
+impl<T: fmt::Display> fmt::Display for Painted<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if !color_mode().is_enabled() {
+			return self.value.fmt(f);
+		}
+
+		let mut codes = Vec::new();
+		if self.bold {
+			codes.push("1".to_string());
+		}
+		if let Some(color) = self.foreground {
+			codes.push((30 + color as u8).to_string());
+		}
+		if let Some(color) = self.background {
+			codes.push((40 + color as u8).to_string());
+		}
+
+		if codes.is_empty() {
+			return self.value.fmt(f);
+		}
+
+		write!(f, "\u{1b}[{}m", codes.join(";"))?;
+		self.value.fmt(f)?;
+		write!(f, "\u{1b}[0m")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::{MeguError, ReadError, ExtensionError, DecodeError, Conflict, ScriptSource, Extension, Namespace, Pattern, Warning, Span};
+	use crate::megu::script::ScriptFormatError;
+	use crate::megu::drop::DropTypeError;
+	use crate::megu::condition::ConditionError;
+	use crate::megu::function::FunctionError;
+	use crate::megu::datapack::DatapackError;
+	use crate::util::{MetaError, OptionError};
+	use std::io;
+	use std::path::PathBuf;
+
+	fn assert_plain(value: impl fmt::Display, expect: &str) {
+		assert_eq!(plain(&value), expect);
+	}
+
+	fn json_error() -> serde_json::Error {
+		serde_json::from_str::<serde_json::Value>("{").unwrap_err()
+	}
+
+	fn conflict() -> Conflict {
+		Conflict {
+			key: Namespace::new("boomber", "ruby"),
+			first: ScriptSource { index: 0, path: Some(PathBuf::from("ruby.ult")) },
+			second: ScriptSource { index: 1, path: None }
+		}
+	}
+
+	#[test]
+	fn render_per_call_take_priority() {
+		let before = color_mode();
+		assert_eq!(plain(&"ruby".cyan()), "ruby");
+		assert_eq!(render(&"ruby".white().on_blue().bold(), ColorMode::Ansi), "\u{1b}[1;37;44mruby\u{1b}[0m");
+		assert_eq!(render(&format_args!("{}", "ruby".red()), ColorMode::Ansi), "\u{1b}[31mruby\u{1b}[0m");
+		assert_eq!(color_mode(), before);
+	}
+
+	#[test]
+	fn library_default_is_plain() {
+		// No test call `set_color_mode()`, so this is the initial global mode
+		assert_eq!(color_mode(), ColorMode::Plain);
+		assert_eq!(ColorMode::default(), ColorMode::Plain);
+		assert_eq!("ruby".cyan().to_string(), "ruby");
+	}
+
+	#[test]
+	fn snapshot_decode_error() {
+		assert_plain(DecodeError::InvalidNamespace("Boomber:ruby".to_string()), r"'Boomber:ruby' contain invalid character (/^[a-z:._\-/\d]+$/)");
+		assert_plain(DecodeError::TooManyColons("a:b:c".to_string()), "'a:b:c' can only contain at most 1 colon.");
+		assert_plain(DecodeError::RegexError(regex::Error::Syntax("bad".to_string())), "bad");
+	}
+
+	#[test]
+	fn snapshot_drop_type_error() {
+		assert_plain(DropTypeError::DecodeError(DecodeError::TooManyColons("a:b:c".to_string())), "'a:b:c' can only contain at most 1 colon.");
+		assert_plain(DropTypeError::NotAllow("minecraft:alternatives".to_string()), "'minecraft:alternatives' is not allow without unsafe keyword.");
+		assert_plain(DropTypeError::InvalidType("minecraft:itme".to_string()), "'minecraft:itme' is not a valid type name.");
+		assert_plain(DropTypeError::Condition(ConditionError::Malformed(serde_json::json!(1))), "'1' is not a valid condition, expected object with condition field.");
+	}
+
+	#[test]
+	fn snapshot_function_error() {
+		let function = || "minecraft:set_count".to_string();
+		assert_plain(FunctionError::Malformed(serde_json::json!([])), "'[]' is not a valid function, expected object with function field.");
+		assert_plain(FunctionError::MissingField { function: function(), field: "count".to_string() }, "'minecraft:set_count' function require 'count' field.");
+		assert_plain(
+			FunctionError::InvalidField { function: function(), field: "count".to_string(), expected: "number provider".to_string() },
+			"'count' field of 'minecraft:set_count' function must be number provider."
+		);
+		assert_plain(FunctionError::UnknownField { function: function(), field: "cuont".to_string() }, "Unknown field 'cuont' in 'minecraft:set_count' function.");
+		assert_plain(
+			FunctionError::Condition(ConditionError::MissingField { condition: "minecraft:random_chance".to_string(), field: "chance".to_string() }),
+			"'minecraft:random_chance' condition require 'chance' field."
+		);
+		assert_plain(DropTypeError::Function(FunctionError::Malformed(serde_json::json!(1))), "'1' is not a valid function, expected object with function field.");
+	}
+
+	#[test]
+	fn snapshot_condition_error() {
+		let condition = || "minecraft:random_chance".to_string();
+		assert_plain(ConditionError::MissingField { condition: condition(), field: "chance".to_string() }, "'minecraft:random_chance' condition require 'chance' field.");
+		assert_plain(
+			ConditionError::InvalidField { condition: condition(), field: "chance".to_string(), expected: "number".to_string() },
+			"'chance' field of 'minecraft:random_chance' condition must be number."
+		);
+		assert_plain(ConditionError::UnknownField { condition: condition(), field: "chanse".to_string() }, "Unknown field 'chanse' in 'minecraft:random_chance' condition.");
+	}
+
+	#[test]
+	fn snapshot_extension_error() {
+		assert_plain(ExtensionError::DecodeError(DecodeError::TooManyColons("a:b:c".to_string())), "'a:b:c' can only contain at most 1 colon.");
+		assert_plain(ExtensionError::NotFound("boomber:ruby".to_string()), "Does not recognized 'boomber:ruby' in extend field.");
+		assert_plain(ExtensionError::Cycle(vec!["boomber:a".to_string(), "boomber:b".to_string(), "boomber:a".to_string()]), "Found cycle in extend field: boomber:a -> boomber:b -> boomber:a");
+	}
+
+	#[test]
+	fn snapshot_script_format_error() {
+		let not_found = || ScriptFormatError::Extension(ExtensionError::NotFound("boomber:ruby".to_string()));
+		assert_plain(not_found(), "Does not recognized 'boomber:ruby' in extend field.");
+		assert_plain(ScriptFormatError::DropType(DropTypeError::InvalidType("itme".to_string())), "'itme' is not a valid type name.");
+		assert_plain(ScriptFormatError::Decode(DecodeError::TooManyColons("a:b:c".to_string())), "'a:b:c' can only contain at most 1 colon.");
+		assert_plain(ScriptFormatError::UnknownField { key: "condtions".to_string(), pool: Some("boomber:ruby".to_string()) }, "Unknown field 'condtions' in 'boomber:ruby' pool.");
+		assert_plain(ScriptFormatError::UnknownField { key: "pool".to_string(), pool: None }, "Unknown field 'pool' in script.");
+		assert_plain(
+			ScriptFormatError::Condition { pool: "boomber:ruby".to_string(), error: Box::new(ConditionError::MissingField { condition: "minecraft:random_chance".to_string(), field: "chance".to_string() }) },
+			"In 'boomber:ruby' pool: 'minecraft:random_chance' condition require 'chance' field."
+		);
+		assert_plain(
+			ScriptFormatError::Function { pool: "boomber:ruby".to_string(), error: Box::new(FunctionError::MissingField { function: "minecraft:set_nbt".to_string(), field: "tag".to_string() }) },
+			"In 'boomber:ruby' pool: 'minecraft:set_nbt' function require 'tag' field."
+		);
+		assert_plain(ScriptFormatError::At(Span::new(3, 5), Box::new(not_found())), "line 3 column 5: Does not recognized 'boomber:ruby' in extend field.");
+		assert_plain(
+			ScriptFormatError::Multiple(vec![not_found(), ScriptFormatError::At(Span::new(4, 1), Box::new(not_found()))]),
+			"Found 2 errors:\n- Does not recognized 'boomber:ruby' in extend field.\n- line 4 column 1: Does not recognized 'boomber:ruby' in extend field."
+		);
+	}
+
+	#[test]
+	fn snapshot_read_error() {
+		assert_plain(ReadError::ScriptFormat(ScriptFormatError::UnknownField { key: "pool".to_string(), pool: None }), "Unknown field 'pool' in script.");
+		assert_plain(ReadError::Io(io::Error::other("disk is full")), "disk is full");
+		assert_plain(ReadError::Serde(json_error()), "EOF while parsing an object at line 1 column 1");
+	}
+
+	#[test]
+	fn snapshot_megu_error() {
+		assert_plain(MeguError::NotExist(PathBuf::from("ruby.ult")), "'ruby.ult' does not exists");
+		assert_plain(MeguError::NotAFile(PathBuf::from("resource")), "'resource' is not a file");
+		assert_plain(MeguError::Read((PathBuf::from("ruby.ult"), ReadError::Serde(json_error()))), "[ruby.ult] EOF while parsing an object at line 1 column 1");
+		assert_plain(MeguError::Merge((Some(PathBuf::from("stone.ult")), ReadError::Serde(json_error()))), "[stone.ult] EOF while parsing an object at line 1 column 1");
+		assert_plain(MeguError::Merge((None, ReadError::Serde(json_error()))), "EOF while parsing an object at line 1 column 1");
+		assert_plain(MeguError::Conflict(conflict()), "'boomber:ruby' is defined in both 'ruby.ult' and 'script #1'.");
+	}
+
+	#[test]
+	fn snapshot_meta_error() {
+		assert_plain(MetaError::NotExist(PathBuf::from("pack.mcmeta")), "'pack.mcmeta' does not exists.");
+		assert_plain(MetaError::NotAFile(PathBuf::from("pack.mcmeta")), "'pack.mcmeta' is directory.");
+		assert_plain(MetaError::Serde((PathBuf::from("pack.mcmeta"), json_error())), "[pack.mcmeta] EOF while parsing an object at line 1 column 1");
+		assert_plain(MetaError::Io((PathBuf::from("pack.mcmeta"), io::Error::other("disk is full"))), "[pack.mcmeta] disk is full");
+		assert_plain(MetaError::NoCompilerOptions(PathBuf::from("pack.mcmeta")), "'pack.mcmeta' does not have compiler_options field.");
+		assert_plain(
+			MetaError::InvalidOption((PathBuf::from("pack.mcmeta"), OptionError { name: "strict".to_string(), value: serde_json::json!("yes") })),
+			"[pack.mcmeta] '\"yes\"' is not a valid value for strict option."
+		);
+	}
+
+	#[test]
+	fn snapshot_datapack_error() {
+		assert_plain(DatapackError::Meta(MetaError::NoCompilerOptions(PathBuf::from("pack.mcmeta"))), "'pack.mcmeta' does not have compiler_options field.");
+		assert_plain(DatapackError::Io((PathBuf::from("data"), io::Error::other("disk is full"))), "[data] disk is full");
+		assert_plain(DatapackError::Megu(MeguError::NotExist(PathBuf::from("ruby.ult"))), "'ruby.ult' does not exists");
+	}
+
+	#[test]
+	fn snapshot_warning() {
+		assert_plain(Warning::Conflict(conflict()), "'boomber:ruby' is defined in both 'ruby.ult' and 'script #1'.");
+		assert_plain(
+			Warning::UnmatchedRemove { pattern: Pattern::decode("boomber:*").unwrap(), script: ScriptSource { index: 0, path: Some(PathBuf::from("ruby.ult")) }, extend: None },
+			"[ruby.ult] 'boomber:*' in remove field does not match any drop."
+		);
+		assert_plain(
+			Warning::UnmatchedRemove {
+				pattern: Pattern::decode("boomber:*").unwrap(),
+				script: ScriptSource { index: 0, path: Some(PathBuf::from("ruby.ult")) },
+				extend: Some(Extension::new("base.ult"))
+			},
+			"[base.ult] 'boomber:*' in remove field does not match any drop, inherited by 'ruby.ult'."
+		);
+		assert_plain(Warning::UnknownOption { name: "minify".to_string(), path: PathBuf::from("pack.mcmeta") }, "[pack.mcmeta] Unknown compiler option 'minify'.");
+	}
+}
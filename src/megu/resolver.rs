@@ -0,0 +1,205 @@
+use super::{Namespace, Extension, ExtensionError, Detector};
+use super::extension::Origin;
+use super::vanilla;
+use std::path::{Path, PathBuf};
+use std::ffi::{OsStr, OsString};
+use std::borrow::Cow;
+
+/// Ordered list of search roots used to resolve `extend` field.
+///
+/// Each root follow `{root}/{prefix}/{suffix}.ult` layout. The first root that contain the file win,
+/// and the embedded vanilla loot table database (with `vanilla` feature) is searched after every root.
+///
+/// Every extension known by `detector()` is tried in order, so `.megu`, `.json.merge` and
+/// extensions registered with `extension()` also work.
+///
+/// A single path can be used anywhere a `Resolver` is expected.
+///
+/// # Examples
+/// ```should_panic
+/// # use rna::Resolver;
+/// # use rna::extension::Origin;
+/// let resolver = Resolver::new()
+///     .root("vanilla")
+///     .root("library")
+///     .root("project");
+///
+/// let extension = resolver.resolve("boomber:blocks/ruby_ore").unwrap();
+/// assert_eq!(extension.origin(), &Origin::Root("library".into()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Resolver {
+	roots: Vec<PathBuf>,
+	detector: Detector
+}
+
+impl Resolver {
+	/// Create resolver without any root
+	pub fn new() -> Resolver {
+		Resolver::default()
+	}
+
+	/// Add `root` after every existing roots
+	pub fn root(mut self, root: impl Into<PathBuf>) -> Resolver {
+		self.push(root);
+		self
+	}
+
+	/// Add `root` after every existing roots
+	pub fn push(&mut self, root: impl Into<PathBuf>) {
+		self.roots.push(root.into());
+	}
+
+	/// Search roots in order
+	pub fn roots(&self) -> &[PathBuf] {
+		&self.roots
+	}
+
+	/// Register extra script extension, see `Detector::extension()`
+	pub fn extension(mut self, extension: impl Into<String>) -> Resolver {
+		self.detector.register(extension);
+		self
+	}
+
+	/// Detector that decide which file names are tried inside each root
+	pub fn detector(&self) -> &Detector {
+		&self.detector
+	}
+
+	/// Find extension with the given Namespace
+	///
+	/// # Errors
+	/// This method can fail when:
+	/// - Namespace is invalid
+	/// - None of the roots (or the embedded database) contain the extension
+	pub fn resolve(&self, value: impl Into<String>) -> Result<Extension, ExtensionError> {
+		let value = value.into();
+		let namespace = Namespace::decode(&value)?;
+
+		for root in &self.roots {
+			for kind in self.detector.kinds() {
+				let path = root
+					.join(&namespace.prefix)
+					.join(format!("{}.{}", namespace.suffix, kind.extension()));
+
+				if path.is_file() {
+					return Ok(Extension::with_origin(value, path, Origin::Root(root.clone())));
+				}
+			}
+		}
+
+		let location = format!("{}/{}.ult", namespace.prefix, namespace.suffix);
+		if vanilla::get(&location).is_some() {
+			return Ok(Extension::with_origin(value, location, Origin::Vanilla));
+		}
+
+		Err(ExtensionError::NotFound(value))
+	}
+}
+
+impl From<PathBuf> for Resolver {
+	fn from(root: PathBuf) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<&PathBuf> for Resolver {
+	fn from(root: &PathBuf) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<&Path> for Resolver {
+	fn from(root: &Path) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<&str> for Resolver {
+	fn from(root: &str) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<String> for Resolver {
+	fn from(root: String) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<&String> for Resolver {
+	fn from(root: &String) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<OsString> for Resolver {
+	fn from(root: OsString) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<&OsStr> for Resolver {
+	fn from(root: &OsStr) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<Box<Path>> for Resolver {
+	fn from(root: Box<Path>) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<Cow<'_, Path>> for Resolver {
+	fn from(root: Cow<'_, Path>) -> Resolver {
+		Resolver::new().root(root)
+	}
+}
+impl From<Vec<PathBuf>> for Resolver {
+	fn from(roots: Vec<PathBuf>) -> Resolver {
+		Resolver { roots, detector: Detector::default() }
+	}
+}
+impl From<&Resolver> for Resolver {
+	fn from(resolver: &Resolver) -> Resolver {
+		resolver.clone()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::megu::temp::TempDir;
+
+	fn create_root(name: &str, files: &[&str]) -> TempDir {
+		let root = TempDir::new(name);
+		for file in files {
+			root.write(file, r#"{"pools":{}}"#);
+		}
+		root
+	}
+
+	#[test]
+	fn first_root_win() {
+		let library = create_root("library", &["boomber/ruby.ult", "boomber/sapphire.ult"]);
+		let project = create_root("project", &["boomber/ruby.ult"]);
+		let resolver = Resolver::new().root(project.path()).root(library.path());
+
+		assert_eq!(resolver.resolve("boomber:ruby").unwrap().origin(), &Origin::Root(project.path().to_path_buf()));
+		assert_eq!(resolver.resolve("boomber:sapphire").unwrap().origin(), &Origin::Root(library.path().to_path_buf()));
+		assert_eq!(resolver.resolve("boomber:emerald"), Err(ExtensionError::NotFound("boomber:emerald".to_string())));
+	}
+
+	#[test]
+	fn try_every_script_extension() {
+		let root = create_root("extension", &["boomber/ruby.json.merge", "boomber/sapphire.loot"]);
+		let resolver = Resolver::from(root.path());
+
+		assert_eq!(resolver.resolve("boomber:ruby").unwrap().location(), root.join("boomber/ruby.json.merge"));
+		assert!(resolver.resolve("boomber:sapphire").is_err());
+		assert_eq!(resolver.extension("loot").resolve("boomber:sapphire").unwrap().location(), root.join("boomber/sapphire.loot"));
+	}
+	#[test]
+	fn convert_from_path_like_types() {
+		let root = PathBuf::from("project");
+		let expect = Resolver::from(root.clone());
+
+		assert_eq!(Resolver::from(&"project".to_string()), expect);
+		assert_eq!(Resolver::from(OsString::from("project")), expect);
+		assert_eq!(Resolver::from(OsStr::new("project")), expect);
+		assert_eq!(Resolver::from(root.clone().into_boxed_path()), expect);
+		assert_eq!(Resolver::from(Cow::Borrowed(root.as_path())), expect);
+	}
+}
@@ -1,61 +1,105 @@
-use super::{Extension, Namespace, DecodeError, MeguDrop, DropFormat, ExtensionError};
+use super::{Extension, Resolver, Namespace, DecodeError, MeguDrop, DropFormat, ExtensionError, Pattern, Removal};
+use super::builder::ScriptBuilder;
 use super::drop;
+use super::condition::ConditionError;
+use super::function::FunctionError;
+use super::span::{self, Span, Location, SourceMap};
 use std::error;
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 /// MeguScript is a data structure for loot table script
+/// 
+/// `pools` keep the order they were defined in, use `sort_pools()` to sort them by key instead.
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct MeguScript {
-	kind: Option<String>,
-	extend: Option<Extension>,
-	pools: HashMap<Namespace, MeguDrop>,
-	remove: Vec<Namespace>
+	pub(crate) kind: Option<String>,
+	pub(crate) extend: Option<Extension>,
+	pub(crate) pools: IndexMap<Namespace, MeguDrop>,
+	pub(crate) remove: Vec<Pattern>,
+	pub(crate) source: Option<PathBuf>
 }
 
-type PoolFormat = Result<HashMap<Namespace, MeguDrop>, drop::DropTypeError>;
+/// Error along with what it point at inside the script
+type Located = (Location, ScriptFormatError);
+type PoolFormat = Result<IndexMap<Namespace, MeguDrop>, Vec<Located>>;
+/// Compiled script with the extension that declared each pattern in its `remove` field, or the file that cause the error
+type Traced = Result<(MeguScript, Vec<Option<Extension>>), (Option<PathBuf>, ReadError)>;
 
 impl MeguScript {
 	/// Create new MeguScript
-	fn new(kind: impl Into<Option<String>>, extend: impl Into<Option<Extension>>, pools: impl Into<HashMap<Namespace, MeguDrop>>, remove: impl Into<Vec<Namespace>>) -> MeguScript {
+	pub(crate) fn new(kind: impl Into<Option<String>>, extend: impl Into<Option<Extension>>, pools: impl Into<IndexMap<Namespace, MeguDrop>>, remove: impl Into<Vec<Pattern>>) -> MeguScript {
 		let kind = kind.into();
 		let extend = extend.into();
 		let pools = pools.into();
 		let remove = remove.into();
-		MeguScript { kind, extend, pools, remove }
+		MeguScript { kind, extend, pools, remove, source: None }
 	}
 
-	/// Convert JSON Template of loot table's pools into `MeguDrop`
-	fn from_pools_format(format: HashMap<String, DropFormat>) -> PoolFormat {
-		let mut result = HashMap::default();
+	/// Convert JSON Template of loot table's pools into `MeguDrop`, collecting every error
+	fn from_pools_format(format: IndexMap<String, DropFormat>, options: ParseOptions) -> PoolFormat {
+		let mut result = IndexMap::default();
+		let mut errors = Vec::default();
 
-		format
-			.into_iter()
-			.map(|(key, value)| (key, MeguDrop::from_drop_format(value)))
-			.try_for_each(|(key, value)| -> Result<(), drop::DropTypeError> {
-				let key = Namespace::decode(key)?;
-				let value = value?;
+		for (key, value) in format {
+			let pointer = span::pointer(&["pools", &key]);
+			let pool = key.clone();
+			let key = Namespace::decode(&key).map_err(|error| errors.push((Location::Key(pointer.clone()), error.into())));
+			let value = MeguDrop::from_drop_format_at(value, &pointer, options).map_err(|error| {
+				errors.extend(error.into_iter().map(|(location, error)| match error {
+					drop::DropTypeError::Condition(error) => (location, ScriptFormatError::Condition { pool: pool.clone(), error: Box::new(error) }),
+					drop::DropTypeError::Function(error) => (location, ScriptFormatError::Function { pool: pool.clone(), error: Box::new(error) }),
+					error => (location, error.into())
+				}));
+			});
 
+			if let (Ok(key), Ok(value)) = (key, value) {
 				result.insert(key, value);
-				Ok(())
-			})?;
+			}
+		}
 
-		Ok(result)
+		if errors.is_empty() {
+			Ok(result)
+		}
+		else {
+			Err(errors)
+		}
 	}
 
 	/// Create new MeguScript from `ScriptFormat` which is a template structure for `serde_json`
-	pub(crate) fn from_script_format(format: ScriptFormat, base_path: impl Into<PathBuf>) -> Result<MeguScript, ScriptFormatError> {
+	pub(crate) fn from_script_format(format: ScriptFormat, resolver: impl Into<Resolver>) -> Result<MeguScript, ScriptFormatError> {
+		MeguScript::from_script_format_at(format, resolver, ParseOptions::default()).map_err(|mut errors| errors.remove(0).1)
+	}
+
+	/// Same as `from_script_format()` but collect every error, each error also point at the value that cause it.
+	/// 
+	/// The returned errors are never empty and follow the order of the script (`extend`, `pools` then `remove`).
+	fn from_script_format_at(format: ScriptFormat, resolver: impl Into<Resolver>, options: ParseOptions) -> Result<MeguScript, Vec<Located>> {
+		let mut errors = Vec::default();
+
 		let kind = format.kind;
 		let extend = match format.extend {
-			Some(value) => Some(Extension::get_extension(value, base_path)?),
+			Some(value) => Extension::get_extension(value, resolver)
+				.map_err(|error| errors.push((Location::Value(span::pointer(&["extend"])), error.into())))
+				.ok(),
 			None => None
 		};
 
-		let pools = MeguScript::from_pools_format(format.pools)?;
-		let remove: Result<Vec<Namespace>, DecodeError> = match format.remove {
-			Some(value) => value.into_iter().map(Namespace::decode).collect(),
-			None => Ok(Vec::default())
-		};
-		let remove = remove?;
+		let pools = MeguScript::from_pools_format(format.pools, options).unwrap_or_else(|mut error| {
+			errors.append(&mut error);
+			IndexMap::default()
+		});
+
+		let mut remove = Vec::default();
+		for (index, value) in format.remove.unwrap_or_default().into_iter().enumerate() {
+			match Pattern::decode(value) {
+				Ok(pattern) => remove.push(pattern),
+				Err(error) => errors.push((Location::Value(format!("/remove/{}", index)), error.into()))
+			}
+		}
+
+		if !errors.is_empty() {
+			return Err(errors);
+		}
 
 		let result = MeguScript::new(kind, extend, pools, remove);
 
@@ -75,18 +119,142 @@ impl MeguScript {
 	/// # use rna::MeguScript;
 	/// let script = MeguScript::from_path("path/to/loot_table.ult", "resource").unwrap();
 	/// ```
-	pub fn from_path(path: impl Into<PathBuf>, base_path: impl Into<PathBuf>) -> Result<MeguScript, ReadError> {
+	pub fn from_path(path: impl Into<PathBuf>, resolver: impl Into<Resolver>) -> Result<MeguScript, ReadError> {
+		MeguScript::from_path_with(path, resolver, ParseOptions::default())
+	}
+
+	/// Create new MeguScript from `path` with the given `ParseOptions`
+	/// 
+	/// # Errors
+	/// Same as `from_path()` and when `options.strict` is `true`:
+	/// - Script or one of its drop contain unknown field (i.e. `"condtions"`)
+	/// - Known condition or function contain unknown field (i.e. `"chacne"`), it's kept as it is otherwise
+	/// 
+	/// # Example
+	/// ```should_panic
+	/// # use rna::MeguScript;
+	/// # use rna::script::ParseOptions;
+	/// let script = MeguScript::from_path_with("path/to/loot_table.ult", "resource", ParseOptions::strict()).unwrap();
+	/// ```
+	pub fn from_path_with(path: impl Into<PathBuf>, resolver: impl Into<Resolver>, options: ParseOptions) -> Result<MeguScript, ReadError> {
 		let path = path.into();
-		let content = fs::read(path)?;
-		let format: ScriptFormat = js::from_slice(&content)?;
-		let result = MeguScript::from_script_format(format, base_path)?;
+		let content = fs::read(&path)?;
+		let mut result = MeguScript::from_slice(&content, resolver, options)?;
+		result.source = Some(path);
 
 		Ok(result)
 	}
 
+	/// Path this script was read from
+	pub fn source(&self) -> Option<&Path> {
+		self.source.as_deref()
+	}
+
+	/// Start building MeguScript, see `ScriptBuilder`
+	pub fn builder() -> ScriptBuilder {
+		ScriptBuilder::default()
+	}
+
+	/// Content of `type` field
+	pub fn kind(&self) -> Option<&str> {
+		self.kind.as_deref()
+	}
+
+	/// Content of `extend` field
+	pub fn extend(&self) -> Option<&Extension> {
+		self.extend.as_ref()
+	}
+
+	/// Content of `pools` field
+	pub fn pools(&self) -> &IndexMap<Namespace, MeguDrop> {
+		&self.pools
+	}
+
+	/// Content of `remove` field
+	pub fn remove(&self) -> &[Pattern] {
+		&self.remove
+	}
+
+	/// Create new MeguScript from raw content of loot table script
+	/// 
+	/// Errors inside `ScriptFormat` are wrapped in `ScriptFormatError::At` with the position of the value that cause it.
+	/// With `options.collect_errors`, every error is returned together in `ScriptFormatError::Multiple`.
+	pub(crate) fn from_slice(content: &[u8], resolver: impl Into<Resolver>, options: ParseOptions) -> Result<MeguScript, ReadError> {
+		let into_error = |errors: Vec<Located>| -> ReadError {
+			let map = SourceMap::new(content);
+			let mut errors: Vec<ScriptFormatError> = errors
+				.into_iter()
+				.map(|(location, error)| match location.resolve(&map) {
+					Some(span) => ScriptFormatError::At(span, Box::new(error)),
+					None => error
+				})
+				.collect();
+
+			if options.collect_errors && errors.len() > 1 {
+				ScriptFormatError::Multiple(errors).into()
+			}
+			else {
+				errors.remove(0).into()
+			}
+		};
+
+		let mut errors = Vec::default();
+		let format: ScriptFormat = if options.strict {
+			let value: js::Value = js::from_slice(content)?;
+			errors = MeguScript::check_fields(&value);
+			if !errors.is_empty() && !options.collect_errors {
+				return Err(into_error(errors));
+			}
+			js::from_value(value)?
+		}
+		else {
+			js::from_slice(content)?
+		};
+
+		match MeguScript::from_script_format_at(format, resolver, options) {
+			Ok(result) if errors.is_empty() => Ok(result),
+			Ok(_) => Err(into_error(errors)),
+			Err(mut rest) => {
+				errors.append(&mut rest);
+				Err(into_error(errors))
+			}
+		}
+	}
+
+	/// Look for every field that `ScriptFormat` and `DropFormat` doesn't know about
+	fn check_fields(value: &js::Value) -> Vec<Located> {
+		let mut result = Vec::default();
+		let script = match value.as_object() {
+			Some(script) => script,
+			None => return result
+		};
+
+		for key in script.keys().filter(|key| !FIELDS.contains(&key.as_str())) {
+			result.push((Location::Key(span::pointer(&[key])), ScriptFormatError::UnknownField { key: key.clone(), pool: None }));
+		}
+
+		let pools = script.get("pools").and_then(js::Value::as_object);
+		for (pool, value) in pools.into_iter().flatten() {
+			let mut fields = Vec::default();
+			drop::unknown_fields(value, &span::pointer(&["pools", pool]), &mut fields);
+
+			for (key, pointer) in fields {
+				result.push((Location::Key(pointer), ScriptFormatError::UnknownField { key, pool: Some(pool.clone()) }));
+			}
+		}
+
+		result
+	}
+
 	/// Merge this script to `other` script.
 	/// This method will mutate `other` but not `self`.
+	/// 
+	/// `other` will inherit this script's `type` if it doesn't have one yet.
 	pub fn merge(&self, other: &mut MeguScript) {
+		if other.kind.is_none() {
+			other.kind = self.kind.clone();
+		}
+
 		for (key, value) in &self.pools {
 			other.pools.insert(key.clone(), value.clone());
 		}
@@ -95,33 +263,122 @@ impl MeguScript {
 	}
 
 	/// Compile `Extension` inside `extend` (if not `None`).
-	pub fn compile(&self, base_path: impl Into<PathBuf>) -> Result<MeguScript, ReadError> {
+	/// 
+	/// `extend` chain of any depth will be compiled, the closest script always take priority.
+	/// `resolver` is used to find `extend` field of the extension itself.
+	/// 
+	/// # Errors
+	/// Beside errors from reading the extensions, this method can fail when the `extend` chain loop back to itself (`ExtensionError::Cycle`).
+	pub fn compile(&self, resolver: impl Into<Resolver>) -> Result<MeguScript, ReadError> {
+		self.compile_with(resolver, ParseOptions::default())
+	}
+
+	/// Same as `compile()` but every script in the `extend` chain is read with the given `ParseOptions`
+	pub fn compile_with(&self, resolver: impl Into<Resolver>, options: ParseOptions) -> Result<MeguScript, ReadError> {
+		self.compile_located(resolver, options).map_err(|(_, error)| error)
+	}
+
+	/// Same as `compile_with()` but the error come with the path of the file that cause it,
+	/// which is `None` when this script wasn't read from a file.
+	pub(crate) fn compile_located(&self, resolver: impl Into<Resolver>, options: ParseOptions) -> Result<MeguScript, (Option<PathBuf>, ReadError)> {
+		self.compile_traced(resolver, options).map(|(result, _)| result)
+	}
+
+	/// Same as `compile_located()` but also return the extension that declared each pattern in `remove` field of the result,
+	/// `None` for the patterns of this script.
+	pub(crate) fn compile_traced(&self, resolver: impl Into<Resolver>, options: ParseOptions) -> Traced {
+		let resolver = resolver.into();
+		self.compile_chain(&resolver, options, &mut Vec::new())
+	}
+
+	/// Compile this script while keeping track of every extension that's already in the chain
+	fn compile_chain(&self, resolver: &Resolver, options: ParseOptions, chain: &mut Vec<Extension>) -> Traced {
 		let mut result: MeguScript = MeguScript::default();
+		let mut origins: Vec<Option<Extension>> = Vec::default();
 
 		if let Some(extension) = &self.extend {
-			let extension = extension.compile(base_path)?;
-			result.kind = extension.kind;
-			result.extend = extension.extend;
-			result.pools = extension.pools;
-			result.remove = extension.remove;
+			if chain.iter().any(|other| other.location() == extension.location()) {
+				let mut names: Vec<String> = chain.iter().map(|other| other.name().to_string()).collect();
+				names.push(extension.name().to_string());
+				return Err((self.source.clone(), ScriptFormatError::Extension(ExtensionError::Cycle(names)).into()));
+			}
+
+			chain.push(extension.clone());
+			let (compiled, declared) = extension
+				.compile_with(resolver, options)
+				.map_err(|error| (Some(extension.location().to_path_buf()), error))?
+				.compile_chain(resolver, options, chain)?;
+			chain.pop();
+
+			result = compiled;
+			origins = declared.into_iter().map(|origin| origin.or_else(|| Some(extension.clone()))).collect();
 		}
 
 		self.merge(&mut result);
+		origins.resize(result.remove.len(), None);
+		if self.kind.is_some() {
+			result.kind = self.kind.clone();
+		}
+		result.source = self.source.clone();
 
-		Ok(result)
+		Ok((result, origins))
 	}
 
-	/// Search through `pools` field and remove any `Drop` that's listed inside `remove` field.
-	pub fn remove_drops(&mut self) -> Vec<Option<MeguDrop>> {
-		self.remove
-			.clone()
+	/// Search through `pools` field and remove any `Drop` that match a pattern inside `remove` field.
+	/// 
+	/// Return which pattern removed which drops, in the same order as `remove` field.
+	/// Every pattern is matched against the pools as they were before any removal,
+	/// so a drop matched by several patterns is listed in each of them.
+	/// Order of the remaining drops is preserved.
+	pub fn remove_drops(&mut self) -> Vec<Removal> {
+		let result: Vec<Removal> = self.remove
 			.iter()
-			.map(|namespace| self.pools.remove(&namespace))
-			.collect()
+			.map(|pattern| {
+				let removed = self.pools
+					.iter()
+					.filter(|(key, _)| pattern.is_match(key))
+					.map(|(key, drop)| (key.clone(), drop.clone()))
+					.collect();
+
+				Removal { pattern: pattern.clone(), removed }
+			})
+			.collect();
+
+		let remove = &self.remove;
+		self.pools.retain(|key, _| !remove.iter().any(|pattern| pattern.is_match(key)));
+		result
+	}
+
+	/// Serialize this script into `.ult` format
+	pub fn to_string(&self) -> Result<String, js::Error> {
+		js::to_string(&ScriptFormat::from(self))
+	}
+
+	/// Serialize this script into human-readable `.ult` format
+	pub fn to_string_pretty(&self) -> Result<String, js::Error> {
+		js::to_string_pretty(&ScriptFormat::from(self))
+	}
+
+	/// Write this script to `path` in `.ult` format, creating parent directories when needed.
+	/// 
+	/// Reading the file back with `from_path()` give the same script.
+	pub fn write(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+		let path: PathBuf = path.into();
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+
+		let content = self.to_string_pretty()?;
+		fs::write(path, content)
+	}
+
+	/// Sort `pools` by key so the output doesn't depend on the order scripts were written or merged in.
+	pub fn sort_pools(&mut self) {
+		self.pools.sort_keys();
 	}
 }
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use serde_json as js;
 impl From<PathBuf> for MeguScript {
@@ -134,15 +391,70 @@ impl From<ScriptFormat> for MeguScript {
 		MeguScript::from_script_format(format, "resource").unwrap()
 	}
 }
+/// Convert MeguScript back into `.ult` template.
+/// 
+/// `extend` is written with the name it was resolved from, so `Extension::new()` will be written as a path.
+impl From<&MeguScript> for ScriptFormat {
+	fn from(script: &MeguScript) -> ScriptFormat {
+		let kind = script.kind.clone();
+		let extend = script.extend.as_ref().map(|extension| extension.name().to_string());
+		let pools = script.pools
+			.iter()
+			.map(|(key, value)| (key.to_string(), DropFormat::from(value)))
+			.collect();
+		let remove = if script.remove.is_empty() {
+			None
+		}
+		else {
+			Some(script.remove.iter().map(|pattern| pattern.to_string()).collect())
+		};
+
+		ScriptFormat { kind, extend, pools, remove }
+	}
+}
+
+/// Options for reading loot table script
+/// 
+/// # Examples
+/// ```
+/// # use rna::script::ParseOptions;
+/// let options = ParseOptions::strict();
+/// assert!(options.strict);
+/// assert!(!ParseOptions::default().strict);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParseOptions {
+	/// Reject any unknown field instead of ignoring it
+	pub strict: bool,
+	/// Keep going after the first error and report every error together
+	pub collect_errors: bool
+}
+
+impl ParseOptions {
+	/// Options that reject unknown fields
+	pub fn strict() -> ParseOptions {
+		ParseOptions { strict: true, ..ParseOptions::default() }
+	}
+
+	/// Options that report every error in the script instead of only the first one
+	pub fn collect_errors() -> ParseOptions {
+		ParseOptions { collect_errors: true, ..ParseOptions::default() }
+	}
+}
+
+/// Every field that `ScriptFormat` understand
+const FIELDS: &[&str] = &["type", "extend", "pools", "remove"];
 
 use serde::{Serialize, Deserialize};
 /// Template structure for `serde_json` to use.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct ScriptFormat {
-	#[serde(rename = "type")]
+	#[serde(rename = "type", skip_serializing_if = "Option::is_none")]
 	pub kind: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extend: Option<String>,
-	pub pools: HashMap<String, DropFormat>,
+	pub pools: IndexMap<String, DropFormat>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub remove: Option<Vec<String>>
 }
 
@@ -154,7 +466,54 @@ pub enum ScriptFormatError {
 	/// DropTypeError emit when there's something wrong in the `type` field in a `MeguDrop`
 	DropType(drop::DropTypeError),
 	/// DecodeError emit when there's an error in Namespace
-	Decode(DecodeError)
+	Decode(DecodeError),
+	/// Emit in strict mode when there's a field that isn't recognized.
+	/// `pool` is `None` if the field is at the top level of the script.
+	UnknownField {
+		key: String,
+		pool: Option<String>
+	},
+	/// Emit when there's something wrong in the `conditions` field of `pool` (or its children)
+	Condition {
+		pool: String,
+		error: Box<ConditionError>
+	},
+	/// Emit when there's something wrong in the `functions` field of `pool` (or its children)
+	Function {
+		pool: String,
+		error: Box<FunctionError>
+	},
+	/// Any of the above errors along with where it is inside the script
+	At(Span, Box<ScriptFormatError>),
+	/// Every error in the script, emit when `ParseOptions::collect_errors` is `true` and there's more than one error
+	Multiple(Vec<ScriptFormatError>)
+}
+
+impl ScriptFormatError {
+	/// Where this error is inside the script (if known), the first error is used for `Multiple`
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			ScriptFormatError::At(span, _) => Some(*span),
+			ScriptFormatError::Multiple(errors) => errors.first().and_then(ScriptFormatError::span),
+			_ => None
+		}
+	}
+
+	/// Every error inside `Multiple`, or this error alone
+	pub fn errors(&self) -> &[ScriptFormatError] {
+		match self {
+			ScriptFormatError::Multiple(errors) => errors,
+			error => std::slice::from_ref(error)
+		}
+	}
+
+	/// This error without its location
+	pub fn inner(&self) -> &ScriptFormatError {
+		match self {
+			ScriptFormatError::At(_, error) => error.inner(),
+			error => error
+		}
+	}
 }
 
 impl From<ExtensionError> for ScriptFormatError {
@@ -178,6 +537,15 @@ impl fmt::Display for ScriptFormatError {
 			ScriptFormatError::Extension(error) => write!(f, "{}", error),
 			ScriptFormatError::DropType(error) => write!(f, "{}", error),
 			ScriptFormatError::Decode(error) => write!(f, "{}", error),
+			ScriptFormatError::UnknownField { key, pool: Some(pool) } => write!(f, "Unknown field '{}' in '{}' pool.", key.red(), pool.cyan()),
+			ScriptFormatError::UnknownField { key, pool: None } => write!(f, "Unknown field '{}' in script.", key.red()),
+			ScriptFormatError::Condition { pool, error } => write!(f, "In '{}' pool: {}", pool.cyan(), error),
+			ScriptFormatError::Function { pool, error } => write!(f, "In '{}' pool: {}", pool.cyan(), error),
+			ScriptFormatError::At(span, error) => write!(f, "{}: {}", span, error),
+			ScriptFormatError::Multiple(errors) => {
+				write!(f, "Found {} errors:", errors.len().to_string().red())?;
+				errors.iter().try_for_each(|error| write!(f, "\n- {}", error))
+			}
 		}
 	}
 }
@@ -195,6 +563,7 @@ pub enum ReadError {
 }
 
 use std::fmt;
+use super::render::Paint;
 impl From<ScriptFormatError> for ReadError {
 	fn from(error: ScriptFormatError) -> ReadError {
 		ReadError::ScriptFormat(error)
@@ -210,6 +579,16 @@ impl From<js::Error> for ReadError {
 		ReadError::Serde(error)
 	}
 }
+impl ReadError {
+	/// Where this error is inside the script (if known)
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			ReadError::ScriptFormat(error) => error.span(),
+			ReadError::Serde(error) if error.line() > 0 => Some(Span::new(error.line(), error.column())),
+			_ => None
+		}
+	}
+}
 impl fmt::Display for ReadError {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
@@ -224,6 +603,8 @@ impl error::Error for ReadError {}
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::megu::condition::Condition;
+	use crate::megu::temp::TempDir;
 /* 
 	#[test]
 	fn create_new_script() {
@@ -231,13 +612,13 @@ mod tests {
 			MeguScript::new(
 				None,
 				Some(Extension::new("minecraft/entities/creeper")),
-				HashMap::default(),
+				IndexMap::default(),
 				Vec::default()
 			),
 			MeguScript {
 				kind: None,
 				extend: Some(Extension::new("minecraft/entities/creeper")),
-				pools: HashMap::default(),
+				pools: IndexMap::default(),
 				remove: Vec::default()
 			}
 		);
@@ -253,12 +634,12 @@ mod tests {
 		}
 		"#;
 		let value: DropFormat = js::from_str(value).unwrap();
-		let mut map: HashMap<String, DropFormat> = HashMap::default();
+		let mut map: IndexMap<String, DropFormat> = IndexMap::default();
 		map.insert("test".to_string(), value);
 
-		let value = MeguScript::from_pools_format(map).unwrap();
+		let value = MeguScript::from_pools_format(map, ParseOptions::default()).unwrap();
 
-		let mut expect: HashMap<Namespace, MeguDrop> = HashMap::default();
+		let mut expect: IndexMap<Namespace, MeguDrop> = IndexMap::default();
 		expect.insert(Namespace::new("minecraft", "test"), MeguDrop::new(
 			DropType::Item,
 			Some("minecraft:emerald".to_string()),
@@ -270,4 +651,287 @@ mod tests {
 
 		assert_eq!(value, expect);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn merge_keep_probabilities() {
+		let value = r#"
+		{
+			"type": "minecraft:chest",
+			"pools": {
+				"boomber:ruby": {
+					"type": "minecraft:item",
+					"name": "boomber:ruby",
+					"rolls": { "min": 1, "max": 2, "type": "minecraft:uniform" },
+					"weight": 3
+				}
+			}
+		}
+		"#;
+		let format: ScriptFormat = js::from_str(value).unwrap();
+		let script = MeguScript::from(format);
+
+		let mut result = MeguScript::default();
+		script.merge(&mut result);
+
+		let drop = &result.pools[&Namespace::new("boomber", "ruby")];
+		assert_eq!(drop.rolls, Some(crate::megu::NumberProvider::Uniform { min: 1.0, max: 2.0 }));
+		assert_eq!(drop.weight, Some(3));
+	}
+
+	#[test]
+	fn strict_mode_reject_unknown_field() {
+		let value = br#"
+		{
+			"pools": {
+				"boomber:ruby": {
+					"type": "minecraft:item",
+					"name": "boomber:ruby",
+					"children": [{ "type": "minecraft:item", "condtions": [] }]
+				}
+			}
+		}
+		"#;
+
+		assert!(MeguScript::from_slice(value, "resource", ParseOptions::default()).is_ok());
+		match MeguScript::from_slice(value, "resource", ParseOptions::strict()) {
+			Err(ReadError::ScriptFormat(error)) => {
+				assert_eq!(error.span(), Some(Span::new(7, 47)));
+				assert_eq!(error.inner(), &ScriptFormatError::UnknownField {
+					key: "condtions".to_string(),
+					pool: Some("boomber:ruby".to_string())
+				});
+			},
+			_ => panic!("expect unknown field error")
+		}
+	}
+
+	#[test]
+	fn strict_mode_reject_unknown_condition_field() {
+		let value = br#"
+		{
+			"pools": {
+				"boomber:ruby": {
+					"type": "minecraft:item",
+					"conditions": [{ "condition": "minecraft:random_chance", "chance": 0.5, "chacne": 0.5 }]
+				}
+			}
+		}
+		"#;
+
+		let script = MeguScript::from_slice(value, "resource", ParseOptions::default()).unwrap();
+		let drop = &script.pools[&Namespace::new("boomber", "ruby")];
+		assert_eq!(drop.conditions, vec![Condition::Other(serde_json::json!({ "condition": "minecraft:random_chance", "chance": 0.5, "chacne": 0.5 }))]);
+
+		match MeguScript::from_slice(value, "resource", ParseOptions::strict()) {
+			Err(ReadError::ScriptFormat(error)) => assert!(matches!(
+				error.inner(),
+				ScriptFormatError::Condition { error, .. } if matches!(**error, ConditionError::UnknownField { .. })
+			)),
+			_ => panic!("expect unknown field error")
+		}
+	}
+
+	#[test]
+	fn locate_invalid_values() {
+		let span = |content: &str| MeguScript::from_slice(content.as_bytes(), "resource", ParseOptions::default()).err().and_then(|error| error.span());
+
+		let key = "{\n  \"pools\": {\n    \"Boomber:ruby\": { \"type\": \"minecraft:item\" }\n  }\n}";
+		assert_eq!(span(key), Some(Span::new(3, 5)));
+
+		let kind = "{\n  \"pools\": {\n    \"boomber:ruby\": {\n      \"type\": \"minecraft:alternatives\", \"unsafe\": true,\n      \"children\": [{ \"type\": \"minecraft:itme\" }]\n    }\n  }\n}";
+		assert_eq!(span(kind), Some(Span::new(5, 30)));
+
+		let remove = "{\n  \"pools\": {},\n  \"remove\": [\"boomber:ruby\", \"Boomber:*\"]\n}";
+		assert_eq!(span(remove), Some(Span::new(3, 30)));
+
+		let condition = "{\n  \"pools\": {\n    \"boomber:ruby\": {\n      \"type\": \"minecraft:item\",\n      \"conditions\": [{ \"condition\": \"minecraft:killed_by_player\" }, { \"condition\": \"minecraft:random_chance\" }]\n    }\n  }\n}";
+		assert_eq!(span(condition), Some(Span::new(5, 69)));
+	}
+
+	#[test]
+	fn name_pool_in_condition_error() {
+		let value = "{\n  \"pools\": {\n    \"boomber:ruby\": {\n      \"type\": \"minecraft:item\",\n      \"conditions\": [{ \"condition\": \"minecraft:random_chance\" }]\n    }\n  }\n}";
+		let error = match MeguScript::from_slice(value.as_bytes(), "resource", ParseOptions::default()) {
+			Err(ReadError::ScriptFormat(error)) => error,
+			result => panic!("expect script format error, found {:?}", result)
+		};
+
+		assert_eq!(error.span(), Some(Span::new(5, 22)));
+		assert_eq!(error.inner(), &ScriptFormatError::Condition {
+			pool: "boomber:ruby".to_string(),
+			error: Box::new(ConditionError::MissingField { condition: "minecraft:random_chance".to_string(), field: "chance".to_string() })
+		});
+	}
+
+	#[test]
+	fn name_pool_in_function_error() {
+		let value = "{\n  \"pools\": {\n    \"boomber:ruby\": {\n      \"type\": \"minecraft:group\", \"unsafe\": true,\n      \"children\": [{ \"type\": \"minecraft:item\", \"functions\": [{ \"function\": \"minecraft:set_count\" }] }]\n    }\n  }\n}";
+		let error = match MeguScript::from_slice(value.as_bytes(), "resource", ParseOptions::default()) {
+			Err(ReadError::ScriptFormat(error)) => error,
+			result => panic!("expect script format error, found {:?}", result)
+		};
+
+		assert_eq!(error.span(), Some(Span::new(5, 62)));
+		assert_eq!(error.inner(), &ScriptFormatError::Function {
+			pool: "boomber:ruby".to_string(),
+			error: Box::new(FunctionError::MissingField { function: "minecraft:set_count".to_string(), field: "count".to_string() })
+		});
+	}
+
+	#[test]
+	fn collect_every_error() {
+		let value = br#"{
+			"extend": "boomber:missing",
+			"pools": {
+				"Boomber:ruby": { "type": "minecraft:item" },
+				"boomber:sapphire": { "type": "minecraft:itme" },
+				"boomber:emerald": {
+					"type": "minecraft:group",
+					"children": [{ "type": "minecraft:alternatives" }, { "type": "minecraft:item" }]
+				}
+			},
+			"remove": ["boomber:*", "Boomber:*"]
+		}"#;
+
+		match MeguScript::from_slice(value, "resource", ParseOptions::default()) {
+			Err(ReadError::ScriptFormat(error)) => assert_eq!(error.inner(), &ScriptFormatError::Extension(ExtensionError::NotFound("boomber:missing".to_string()))),
+			_ => panic!("expect extension error")
+		}
+
+		match MeguScript::from_slice(value, "resource", ParseOptions::collect_errors()) {
+			Err(ReadError::ScriptFormat(error)) => {
+				let lines: Vec<usize> = error.errors().iter().filter_map(|error| error.span()).map(|span| span.line).collect();
+				assert_eq!(lines, [2, 4, 5, 7, 8, 11]);
+				assert!(matches!(error.errors()[3].inner(), ScriptFormatError::DropType(drop::DropTypeError::NotAllow(_))));
+				assert!(matches!(error.errors()[4].inner(), ScriptFormatError::DropType(drop::DropTypeError::NotAllow(_))));
+			},
+			_ => panic!("expect multiple errors")
+		}
+	}
+
+	#[test]
+	fn compile_extend_chain() {
+		let root = TempDir::new("chain");
+		root.write("boomber/grandparent.ult", r#"{"type":"minecraft:block","pools":{"boomber:coal":{"type":"minecraft:item","name":"minecraft:coal"}}}"#);
+		root.write("boomber/parent.ult", r#"{"extend":"boomber:grandparent","pools":{"boomber:iron":{"type":"minecraft:item","name":"minecraft:iron_ingot"}}}"#);
+
+		let format: ScriptFormat = js::from_str(r#"{"extend":"boomber:parent","pools":{}}"#).unwrap();
+		let script = MeguScript::from_script_format(format, root.path()).unwrap();
+		let result = script.compile(root.path()).unwrap();
+
+		assert_eq!(result.kind, Some("minecraft:block".to_string()));
+		assert!(result.pools.contains_key(&Namespace::new("boomber", "coal")));
+		assert!(result.pools.contains_key(&Namespace::new("boomber", "iron")));
+	}
+
+	#[test]
+	fn detect_extend_cycle() {
+		let root = TempDir::new("cycle");
+		root.write("boomber/a.ult", r#"{"extend":"boomber:b","pools":{}}"#);
+		root.write("boomber/b.ult", r#"{"extend":"boomber:a","pools":{}}"#);
+
+		let format: ScriptFormat = js::from_str(r#"{"extend":"boomber:a","pools":{}}"#).unwrap();
+		let script = MeguScript::from_script_format(format, root.path()).unwrap();
+
+		match script.compile(root.path()) {
+			Err(ReadError::ScriptFormat(ScriptFormatError::Extension(error))) => assert_eq!(
+				error,
+				ExtensionError::Cycle(vec!["boomber:a".to_string(), "boomber:b".to_string(), "boomber:a".to_string()])
+			),
+			_ => panic!("expect cycle error")
+		}
+		assert_eq!(script.compile_located(root.path(), ParseOptions::default()).unwrap_err().0, Some(root.join("boomber/b.ult")));
+	}
+
+	#[test]
+	fn locate_error_in_extend_chain() {
+		use crate::megu::{merge, MeguError};
+		use crate::megu::diagnostic::ToDiagnostics;
+
+		let root = TempDir::new("located");
+		let broken = root.write("boomber/broken.ult", r#"{"pools":{"boomber:coal":{"type":"minecraft:itme"}}}"#);
+		root.write("boomber/parent.ult", r#"{"extend":"boomber:broken","pools":{}}"#);
+
+		let format: ScriptFormat = js::from_str(r#"{"extend":"boomber:parent","pools":{}}"#).unwrap();
+		let script = MeguScript::from_script_format(format, root.path()).unwrap();
+
+		match merge(&[script], root.path()).map(|(result, _)| result) {
+			Err(error @ MeguError::Merge(_)) => {
+				assert!(matches!(&error, MeguError::Merge((Some(path), _)) if path == &broken));
+				assert_eq!(error.to_diagnostics()[0].file.as_ref(), Some(&broken));
+			},
+			_ => panic!("expect merge error")
+		}
+	}
+
+	#[test]
+	fn keep_pool_order() {
+		let value = r#"{"pools":{"boomber:c":{"type":"minecraft:empty"},"boomber:a":{"type":"minecraft:empty"},"boomber:b":{"type":"minecraft:empty"}},"remove":["boomber:a"]}"#;
+		let format: ScriptFormat = js::from_str(value).unwrap();
+		let mut script = MeguScript::from(format);
+		let keys = |script: &MeguScript| script.pools.keys().map(|key| key.suffix.clone()).collect::<Vec<_>>();
+
+		assert_eq!(keys(&script), ["c", "a", "b"]);
+
+		let removals = script.remove_drops();
+		assert_eq!(removals[0].removed[0].0, Namespace::new("boomber", "a"));
+		assert_eq!(keys(&script), ["c", "b"]);
+
+		script.sort_pools();
+		assert_eq!(keys(&script), ["b", "c"]);
+	}
+
+	#[cfg(feature = "vanilla")]
+	#[test]
+	fn round_trip_every_vanilla_script() {
+		for path in crate::megu::vanilla::paths() {
+			let content = crate::megu::vanilla::get(path).unwrap();
+			let script = MeguScript::from_slice(content, "resource", ParseOptions::strict()).unwrap();
+			let written = script.to_string().unwrap();
+
+			let original: js::Value = js::from_slice(content).unwrap();
+			let value: js::Value = js::from_str(&written).unwrap();
+			assert_eq!(value, original, "{} does not round-trip", path);
+
+			let read_back = MeguScript::from_slice(written.as_bytes(), "resource", ParseOptions::strict()).unwrap();
+			assert_eq!(read_back, script, "{} does not round-trip", path);
+		}
+	}
+
+	#[test]
+	fn round_trip_minimal_script() {
+		let content = br#"
+		{
+			"pools": {
+				"boomber:ruby": { "type": "minecraft:item", "name": "boomber:ruby" },
+				"boomber:sapphire": {
+					"unsafe": false,
+					"type": "minecraft:item",
+					"name": "boomber:sapphire",
+					"conditions": [],
+					"functions": [
+						{ "function": "minecraft:looting_enchant", "count": { "min": 0, "max": 1.0, "type": "minecraft:uniform" } },
+						{ "function": "minecraft:set_count", "count": 2.0, "comment": "stack" }
+					],
+					"rolls": { "min": 1.0, "max": 2, "comment": "untyped" },
+					"bonus_rolls": 1.0
+				}
+			}
+		}
+		"#;
+
+		let script = MeguScript::from_slice(content, "resource", ParseOptions::default()).unwrap();
+		let original: js::Value = js::from_slice(content).unwrap();
+		let value: js::Value = js::from_str(&script.to_string().unwrap()).unwrap();
+		assert_eq!(value, original);
+
+		let mut script = script;
+		let drop = script.pools.get_mut(&Namespace::new("boomber", "sapphire")).unwrap();
+		drop.rolls = Some(crate::megu::NumberProvider::Constant(3.0));
+		drop.functions.clear();
+		let value: js::Value = js::from_str(&script.to_string().unwrap()).unwrap();
+		assert_eq!(value["pools"]["boomber:sapphire"]["rolls"], js::json!(3));
+		assert_eq!(value["pools"]["boomber:sapphire"]["bonus_rolls"], js::json!(1.0));
+		assert_eq!(value["pools"]["boomber:sapphire"]["functions"], js::json!([]));
+	}
+}
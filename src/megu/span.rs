@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+/// Position inside loot table script, both `line` and `column` start at 1
+///
+/// `column` is counted in characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Span {
+	pub line: usize,
+	pub column: usize
+}
+
+impl Span {
+	pub fn new(line: usize, column: usize) -> Span {
+		Span { line, column }
+	}
+}
+
+use std::fmt;
+impl fmt::Display for Span {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "line {} column {}", self.line, self.column)
+	}
+}
+
+/// What an error point at inside the script, resolved into `Span` with `SourceMap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Location {
+	/// Object key at this JSON pointer (i.e. pool key)
+	Key(String),
+	/// Value at this JSON pointer (i.e. `type` field)
+	Value(String)
+}
+
+impl Location {
+	pub(crate) fn resolve(&self, map: &SourceMap) -> Option<Span> {
+		match self {
+			Location::Key(pointer) => map.keys.get(pointer).copied(),
+			Location::Value(pointer) => map.values.get(pointer).copied()
+		}
+	}
+}
+
+/// Build JSON pointer from `segments` (i.e. `["pools", "boomber:ruby"]` become `/pools/boomber:ruby`)
+pub(crate) fn pointer<S: AsRef<str>>(segments: &[S]) -> String {
+	segments
+		.iter()
+		.map(|segment| format!("/{}", segment.as_ref().replace('~', "~0").replace('/', "~1")))
+		.collect()
+}
+
+/// Span of every key and value inside JSON document, addressed by JSON pointer
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct SourceMap {
+	keys: HashMap<String, Span>,
+	values: HashMap<String, Span>
+}
+
+impl SourceMap {
+	/// Scan `content` for positions, stop silently at the first syntax error.
+	///
+	/// This is only used after `serde_json` already accept `content` so it doesn't report any error itself.
+	pub(crate) fn new(content: &[u8]) -> SourceMap {
+		let mut scanner = Scanner { content, position: 0, line: 1, column: 1, map: SourceMap::default() };
+		scanner.value(String::new());
+		scanner.map
+	}
+}
+
+struct Scanner<'a> {
+	content: &'a [u8],
+	position: usize,
+	line: usize,
+	column: usize,
+	map: SourceMap
+}
+
+impl Scanner<'_> {
+	fn peek(&self) -> Option<u8> {
+		self.content.get(self.position).copied()
+	}
+
+	fn bump(&mut self) -> Option<u8> {
+		let byte = self.peek()?;
+		self.position += 1;
+
+		if byte == b'\n' {
+			self.line += 1;
+			self.column = 1;
+		}
+		// UTF-8 continuation bytes belong to the previous character
+		else if byte & 0xC0 != 0x80 {
+			self.column += 1;
+		}
+
+		Some(byte)
+	}
+
+	fn span(&self) -> Span {
+		Span::new(self.line, self.column)
+	}
+
+	fn skip_whitespace(&mut self) {
+		while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+			self.bump();
+		}
+	}
+
+	fn eat(&mut self, expect: u8) -> Option<()> {
+		self.skip_whitespace();
+		if self.peek()? == expect {
+			self.bump();
+			return Some(());
+		}
+
+		None
+	}
+
+	fn value(&mut self, pointer: String) -> Option<()> {
+		self.skip_whitespace();
+		self.map.values.insert(pointer.clone(), self.span());
+
+		match self.peek()? {
+			b'{' => self.object(pointer),
+			b'[' => self.array(pointer),
+			b'"' => self.string().map(|_| ()),
+			_ => {
+				while !matches!(self.peek()?, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+					self.bump();
+				}
+				Some(())
+			}
+		}
+	}
+
+	fn object(&mut self, pointer: String) -> Option<()> {
+		self.eat(b'{')?;
+		if self.eat(b'}').is_some() {
+			return Some(());
+		}
+
+		loop {
+			self.skip_whitespace();
+			let span = self.span();
+			let key = self.string()?;
+			let child = format!("{}{}", pointer, self::pointer(&[key]));
+			self.map.keys.insert(child.clone(), span);
+
+			self.eat(b':')?;
+			self.value(child)?;
+
+			if self.eat(b',').is_none() {
+				return self.eat(b'}');
+			}
+		}
+	}
+
+	fn array(&mut self, pointer: String) -> Option<()> {
+		self.eat(b'[')?;
+		if self.eat(b']').is_some() {
+			return Some(());
+		}
+
+		for index in 0.. {
+			self.value(format!("{}/{}", pointer, index))?;
+
+			if self.eat(b',').is_none() {
+				break;
+			}
+		}
+
+		self.eat(b']')
+	}
+
+	/// Read string literal, the result is only correct for valid JSON
+	fn string(&mut self) -> Option<String> {
+		self.eat(b'"')?;
+		let mut bytes = Vec::new();
+
+		loop {
+			match self.bump()? {
+				b'"' => break,
+				b'\\' => match self.bump()? {
+					b'n' => bytes.push(b'\n'),
+					b't' => bytes.push(b'\t'),
+					b'r' => bytes.push(b'\r'),
+					b'b' => bytes.push(0x08),
+					b'f' => bytes.push(0x0C),
+					b'u' => {
+						let mut code = self.hex()?;
+						if (0xD800..0xDC00).contains(&code) && self.bump() == Some(b'\\') && self.bump() == Some(b'u') {
+							let low = self.hex()?;
+							code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+						}
+						let c = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+						bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+					},
+					other => bytes.push(other)
+				},
+				other => bytes.push(other)
+			}
+		}
+
+		Some(String::from_utf8_lossy(&bytes).into_owned())
+	}
+
+	fn hex(&mut self) -> Option<u32> {
+		let mut result = 0;
+		for _ in 0..4 {
+			let digit = char::from(self.bump()?).to_digit(16)?;
+			result = result * 16 + digit;
+		}
+
+		Some(result)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn locate_keys_and_values() {
+		let content = "{\n\t\"pools\": {\n\t\t\"boomber:gems/ruby\": { \"type\": \"minecraft:itme\" }\n\t},\n\t\"remove\": [\"a\", \"é\", \"b\"]\n}";
+		let map = SourceMap::new(content.as_bytes());
+		let ruby = pointer(&["pools", "boomber:gems/ruby"]);
+
+		assert_eq!(ruby, "/pools/boomber:gems~1ruby");
+		assert_eq!(Location::Key(ruby.clone()).resolve(&map), Some(Span::new(3, 3)));
+		assert_eq!(Location::Value(format!("{}/type", ruby)).resolve(&map), Some(Span::new(3, 34)));
+		assert_eq!(Location::Value("/remove/2".to_string()).resolve(&map), Some(Span::new(5, 23)));
+		assert_eq!(Location::Value("/remove/3".to_string()).resolve(&map), None);
+	}
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Temporary directory for tests, unique to each `TempDir::new()` call and removed with everything inside when dropped
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+	/// Create an empty directory, `name` only make it easier to recognize
+	pub(crate) fn new(name: &str) -> TempDir {
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+		let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+		let path = std::env::temp_dir().join(format!("rna-test-{}-{}-{}", std::process::id(), id, name));
+		let _ = fs::remove_dir_all(&path);
+		fs::create_dir_all(&path).unwrap();
+
+		TempDir(path)
+	}
+
+	pub(crate) fn path(&self) -> &Path {
+		&self.0
+	}
+
+	/// Write `content` into `file` (relative to this directory) and create any missing parent directory
+	pub(crate) fn write(&self, file: impl AsRef<Path>, content: &str) -> PathBuf {
+		let path = self.0.join(file);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(&path, content).unwrap();
+
+		path
+	}
+
+	/// Path of `file` inside this directory
+	pub(crate) fn join(&self, file: impl AsRef<Path>) -> PathBuf {
+		self.0.join(file)
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
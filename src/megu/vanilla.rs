@@ -0,0 +1,88 @@
+//! Vanilla loot table database embedded from `precompiled_vanilla_loot_table.tar.gz`
+//!
+//! Every file is stored as `{prefix}/{suffix}.ult` (i.e. `minecraft/entities/creeper.ult`).
+//! Only available with `vanilla` feature, otherwise the database is always empty.
+
+#[cfg(feature = "vanilla")]
+use std::collections::HashMap;
+#[cfg(feature = "vanilla")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "vanilla")]
+static ARCHIVE: &[u8] = include_bytes!("../../precompiled_vanilla_loot_table.tar.gz");
+
+/// Unpack the archive on first use
+#[cfg(feature = "vanilla")]
+fn database() -> &'static HashMap<String, Vec<u8>> {
+	static DATABASE: OnceLock<HashMap<String, Vec<u8>>> = OnceLock::new();
+
+	DATABASE.get_or_init(|| {
+		use std::io::Read;
+		let mut result = HashMap::default();
+		let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(ARCHIVE));
+		let entries = archive.entries().expect("embedded vanilla loot table archive is corrupted");
+
+		for entry in entries {
+			let mut entry = entry.expect("embedded vanilla loot table archive is corrupted");
+			if !entry.header().entry_type().is_file() {
+				continue;
+			}
+
+			let path = match entry.path().ok().and_then(|path| path.to_str().map(String::from)) {
+				Some(path) => path,
+				None => continue
+			};
+			let mut content = Vec::new();
+			entry.read_to_end(&mut content).expect("embedded vanilla loot table archive is corrupted");
+
+			result.insert(path, content);
+		}
+
+		result
+	})
+}
+
+/// Get the content of embedded loot table script at `path`
+///
+/// # Examples
+/// ```
+/// # use rna::vanilla;
+/// assert!(vanilla::get("minecraft/entities/creeper.ult").is_some());
+/// assert!(vanilla::get("minecraft/entities/megumin.ult").is_none());
+/// ```
+#[cfg(feature = "vanilla")]
+pub fn get(path: &str) -> Option<&'static [u8]> {
+	database().get(path).map(Vec::as_slice)
+}
+
+/// Get the content of embedded loot table script at `path`
+#[cfg(not(feature = "vanilla"))]
+pub fn get(_path: &str) -> Option<&'static [u8]> {
+	None
+}
+
+/// Path of every embedded loot table script
+#[cfg(feature = "vanilla")]
+pub fn paths() -> Vec<&'static str> {
+	let mut result: Vec<&str> = database().keys().map(String::as_str).collect();
+	result.sort_unstable();
+	result
+}
+
+#[cfg(all(test, feature = "vanilla"))]
+mod tests {
+	use super::*;
+	use crate::megu::{MeguScript, ParseOptions};
+
+	#[test]
+	fn every_embedded_script_is_valid() {
+		assert!(!paths().is_empty());
+
+		for path in paths() {
+			let content = get(path).unwrap();
+			if let Err(error) = MeguScript::from_slice(content, "resource", ParseOptions::strict()) {
+				panic!("[{}] {}", path, error);
+			}
+		}
+	}
+}
@@ -2,21 +2,136 @@ use std::path::PathBuf;
 use std::fs;
 use serde_json as js;
 use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::megu::{ConflictPolicy, ParseOptions, MergeOptions, Detector};
 
 #[derive(Serialize, Deserialize)]
 struct MetaFormat {
 	compiler_options: Option<Vec<CompilerOption>>
 }
 
-#[derive(Serialize, Deserialize)]
-struct CompilerOption {
-	name: String
+/// A single entry inside `compiler_options` field
+///
+/// `value` can be anything, it's validated by `CompilerOptions::from_options()`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompilerOption {
+	pub name: String,
+	#[serde(default, skip_serializing_if = "Value::is_null")]
+	pub value: Value
+}
+
+/// Typed model of `compiler_options` field.
+///
+/// Known options are:
+/// - `conflict_policy`: `"error"`, `"warn"` (default), `"keep_first"` or `"keep_last"`
+/// - `strict`: boolean, reject unknown fields
+/// - `collect_errors`: boolean, report every error in a script instead of only the first one
+/// - `sort_pools`: boolean, sort pools by key
+///
+/// Boolean options without `value` are treated as `true`. Options that are not set stay `None`.
+///
+/// # Examples
+/// ```
+/// # use rna::utils::{CompilerOption, CompilerOptions};
+/// # use rna::conflict::ConflictPolicy;
+/// # use serde_json::json;
+/// let options = CompilerOptions::from_options(&[
+///     CompilerOption { name: "conflict_policy".to_string(), value: json!("warn") },
+///     CompilerOption { name: "strict".to_string(), value: json!(null) },
+///     CompilerOption { name: "minify".to_string(), value: json!(true) }
+/// ]).unwrap();
+///
+/// assert_eq!(options.merge_options().conflict_policy, ConflictPolicy::Warn);
+/// assert!(options.parse_options().strict);
+/// assert_eq!(options.unknown, ["minify"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompilerOptions {
+	pub conflict_policy: Option<ConflictPolicy>,
+	pub strict: Option<bool>,
+	pub collect_errors: Option<bool>,
+	pub sort_pools: Option<bool>,
+	/// Name of every option that this library doesn't know
+	pub unknown: Vec<String>
+}
+
+impl CompilerOptions {
+	/// Validate `options` and turn them into typed model
+	///
+	/// # Errors
+	/// This method can fail when a known option have invalid value.
+	pub fn from_options(options: &[CompilerOption]) -> Result<CompilerOptions, OptionError> {
+		let mut result = CompilerOptions::default();
+
+		for option in options {
+			let invalid = || OptionError { name: option.name.clone(), value: option.value.clone() };
+			let flag = || match &option.value {
+				Value::Null => Ok(true),
+				Value::Bool(value) => Ok(*value),
+				_ => Err(invalid())
+			};
+
+			match option.name.as_str() {
+				"conflict_policy" => {
+					let policy = option.value.as_str().and_then(|value| value.parse().ok()).ok_or_else(invalid)?;
+					result.conflict_policy = Some(policy);
+				},
+				"strict" => result.strict = Some(flag()?),
+				"collect_errors" => result.collect_errors = Some(flag()?),
+				"sort_pools" => result.sort_pools = Some(flag()?),
+				_ => result.unknown.push(option.name.clone())
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Override options in `self` with every option that is set in `other`
+	pub fn extend(&mut self, other: &CompilerOptions) {
+		self.conflict_policy = other.conflict_policy.or(self.conflict_policy);
+		self.strict = other.strict.or(self.strict);
+		self.collect_errors = other.collect_errors.or(self.collect_errors);
+		self.sort_pools = other.sort_pools.or(self.sort_pools);
+		self.unknown.extend(other.unknown.iter().cloned());
+	}
+
+	/// `ParseOptions` with these options applied on top of the default
+	pub fn parse_options(&self) -> ParseOptions {
+		ParseOptions {
+			strict: self.strict.unwrap_or_default(),
+			collect_errors: self.collect_errors.unwrap_or_default()
+		}
+	}
+
+	/// `MergeOptions` with these options applied on top of the default
+	pub fn merge_options(&self) -> MergeOptions {
+		MergeOptions {
+			conflict_policy: self.conflict_policy.unwrap_or_default(),
+			sort_pools: self.sort_pools.unwrap_or_default(),
+			parse_options: self.parse_options()
+		}
+	}
+}
+
+/// Known compiler option with invalid value
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionError {
+	pub name: String,
+	pub value: Value
 }
 
 /// Shorthand for `Result<(), MetaError>`
 pub type MetaResult = Result<(), MetaError>;
 /// Check `pack.mcmeta` file for `compiler_options` field
 pub fn check_meta(path: impl Into<PathBuf>) -> MetaResult {
+	read_meta(path).map(|_| ())
+}
+
+/// Read `compiler_options` field from `pack.mcmeta` file
+///
+/// # Errors
+/// This method can fail for the same reasons as `check_meta()` or when a known option have invalid value.
+pub fn read_meta(path: impl Into<PathBuf>) -> Result<CompilerOptions, MetaError> {
 	let path: PathBuf = path.into();
 	if !path.exists() {
 		return Err(MetaError::NotExist(path))
@@ -35,11 +150,12 @@ pub fn check_meta(path: impl Into<PathBuf>) -> MetaResult {
 		Err(error) => return Err(MetaError::Serde((path, error)))
 	};
 
-	if interpret.compiler_options.is_none() {
-		return Err(MetaError::NoCompilerOptions(path));
-	}
+	let options = match interpret.compiler_options {
+		Some(options) => options,
+		None => return Err(MetaError::NoCompilerOptions(path))
+	};
 
-	Ok(())
+	CompilerOptions::from_options(&options).map_err(|error| MetaError::InvalidOption((path, error)))
 }
 
 use std::io;
@@ -55,10 +171,12 @@ pub enum MetaError {
 	/// Emit when I/O error occur
 	Io((PathBuf, io::Error)),
 	/// Emit when `compiler_options` is not found inside `path`
-	NoCompilerOptions(PathBuf)
+	NoCompilerOptions(PathBuf),
+	/// Emit when known compiler option inside `path` have invalid value
+	InvalidOption((PathBuf, OptionError))
 }
 
-use colored::*;
+use crate::megu::render::Paint;
 use std::fmt;
 impl fmt::Display for MetaError {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -68,22 +186,20 @@ impl fmt::Display for MetaError {
 			MetaError::Serde((path, error)) => write!(f, "[{}] {}", path.display().to_string().green(), error),
 			MetaError::Io((path, error)) => write!(f, "[{}] {}", path.display().to_string().green(), error),
 			MetaError::NoCompilerOptions(path) => write!(f, "'{}' does not have {} field.", path.display().to_string().cyan(), "compiler_options".white().on_blue()),
+			MetaError::InvalidOption((path, error)) => write!(f, "[{}] {}", path.display().to_string().green(), error),
 		}
 	}
 }
 
-/// Check if `path` has the correct extension for loot table script
-pub fn is_loot_table_script(path: impl Into<PathBuf>) -> bool {
-	let path: PathBuf = path.into();
-
-	if let Some(extension) = path.extension() {
-		if let Some(extension) = extension.to_str() {
-			match extension {
-				"ult" | "json.merge" | "megu" => return true,
-				_ => false
-			};
-		};
+impl fmt::Display for OptionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "'{}' is not a valid value for {} option.", self.value.to_string().cyan(), self.name.white().on_blue())
 	}
+}
 
-	false
-}
\ No newline at end of file
+/// Check if `path` has the correct extension for loot table script
+///
+/// Only `.ult`, `.megu` and `.json.merge` are recognized, use `Detector` to register extra extensions.
+pub fn is_loot_table_script(path: impl Into<PathBuf>) -> bool {
+	Detector::default().detect(path.into()).is_some()
+}